@@ -0,0 +1,149 @@
+use super::*;
+
+/// Add to a story entity to have it continue by itself one line every
+/// `interval`, e.g., for a timed cutscene readout. Each line raises
+/// [InkEvent::OnContinue] as with [InkStories::cont]. It pauses at choices and
+/// once the story ends, and carries on a full `interval` after it can
+/// continue again.
+///
+/// Time is read from Bevy's virtual [Time], never the wall clock, so pausing
+/// it pauses playback. A test can step it exactly by inserting
+/// `TimeUpdateStrategy::ManualDuration(step)`: each `app.update()` after the
+/// first then advances `elapsed` by `step`.
+#[derive(Debug, Component, Clone)]
+pub struct AutoPlay {
+    pub interval: Duration,
+    /// Time since the last line, or since the story could continue again.
+    pub elapsed: Duration,
+}
+
+impl AutoPlay {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            elapsed: Duration::ZERO,
+        }
+    }
+}
+
+/// Continues each [AutoPlay] story whose interval is up.
+pub fn auto_play(
+    mut ink_stories: NonSendMut<InkStories>,
+    mut query: Query<(Entity, &mut AutoPlay), With<InkStory>>,
+    time: Res<Time>,
+) {
+    for (id, mut auto_play) in &mut query {
+        if !ink_stories.can_continue(id).unwrap_or(false) {
+            auto_play.elapsed = Duration::ZERO;
+            continue;
+        }
+        auto_play.elapsed += time.delta();
+        if auto_play.elapsed < auto_play.interval {
+            continue;
+        }
+        auto_play.elapsed = Duration::ZERO;
+        if let Err(err) = ink_stories.cont(id) {
+            warn!("Could not auto-play ink story {id}: {err}");
+        }
+    }
+}
+
+/// Send to continue a story one line, e.g., from an input system without
+/// access to [InkStories]. [advance_on_request] continues it with
+/// [InkStories::cont], which raises [InkEvent::OnContinue] and, once the story
+/// stops for choices, [InkEvent::OnChoicePoint]. A story that can't continue
+/// ignores the request.
+#[derive(Debug, Event, Clone, Copy)]
+pub struct InkAdvanceRequest(pub Entity);
+
+/// Continues each story an [InkAdvanceRequest] names.
+pub fn advance_on_request(
+    mut ink_stories: NonSendMut<InkStories>,
+    mut requests: EventReader<InkAdvanceRequest>,
+) {
+    for &InkAdvanceRequest(id) in requests.read() {
+        if !ink_stories.can_continue(id).unwrap_or(false) {
+            continue;
+        }
+        if let Err(err) = ink_stories.cont(id) {
+            warn!("Could not advance ink story {id}: {err}");
+        }
+    }
+}
+
+/// Add to a story entity to have it take a choice by itself whenever it's
+/// the only one on offer, then continue a line, as though a player picked
+/// it. Choosing and continuing raise [InkEvent::OnChoicesChanged] and
+/// [InkEvent::OnContinue] as usual. A run of single choices with no text
+/// between them stops after [InkStories::max_continues] choices.
+#[derive(Debug, Component, Clone, Copy, Default)]
+pub struct AutoSelectSingleChoice;
+
+/// Takes the lone choice of each [AutoSelectSingleChoice] story.
+pub fn auto_select_single_choices(
+    mut ink_stories: NonSendMut<InkStories>,
+    query: Query<Entity, (With<AutoSelectSingleChoice>, With<InkStory>)>,
+) {
+    for id in &query {
+        for _ in 0..ink_stories.max_continues {
+            if ink_stories.get(id).is_ok_and(|story| story.can_continue()) {
+                break;
+            }
+            let choices = ink_stories.visible_choices(id).unwrap_or_default();
+            let [choice] = choices.as_slice() else {
+                break;
+            };
+            if let Err(err) = ink_stories
+                .choose(id, choice)
+                .and_then(|_| ink_stories.cont(id))
+            {
+                warn!("Could not auto-select ink choice in {id}: {err}");
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::time::TimeUpdateStrategy;
+
+    const LINES: &str = include_str!("../assets/lines.ink.json");
+
+    #[test]
+    fn auto_play_steps_on_the_virtual_clock() {
+        let mut app = crate::tests::app();
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            100,
+        )));
+        let handle = app
+            .world_mut()
+            .resource_mut::<Assets<InkText>>()
+            .add(InkText(LINES.into(), InkFormat::Json));
+        let id = app
+            .world_mut()
+            .spawn((InkLoad(handle), AutoPlay::new(Duration::from_millis(300))))
+            .id();
+        let text = |app: &App| {
+            app.world()
+                .non_send_resource::<InkStories>()
+                .current_text(id)
+                .unwrap_or_default()
+        };
+        // The story loads over the first updates; after that each update
+        // adds 100 ms, so a line comes every third one.
+        app.update();
+        app.update();
+        assert_eq!(text(&app), "");
+        app.update();
+        app.update();
+        assert_eq!(text(&app), "Hello\n");
+        for _ in 0..2 {
+            app.update();
+        }
+        assert_eq!(text(&app), "Hello\n");
+        app.update();
+        assert_eq!(text(&app), "World\n");
+    }
+}
@@ -0,0 +1,191 @@
+use super::*;
+use bladeink::{story::external_functions::ExternalFunction, value_type::ValueType};
+
+/// An `EXTERNAL` call bound with [InkStories::bind_async_external] that
+/// waits on [InkStories::resolve_external].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingExternal {
+    pub name: String,
+    pub args: Vec<VariableValue>,
+}
+
+/// The calls to a story's async externals while it continues a line.
+#[derive(Debug, Default)]
+pub(crate) struct AsyncCalls {
+    /// The values resolved so far for the line, in the order it calls them.
+    resolved: Vec<VariableValue>,
+    /// How many of `resolved` the current try at the line has used.
+    used: usize,
+    /// The first call the current try found no value for.
+    unresolved: Option<PendingExternal>,
+    /// The call the line waits on.
+    awaiting: Option<PendingExternal>,
+}
+
+impl AsyncCalls {
+    /// Forgets the line being continued, e.g., when the story starts over.
+    pub(crate) fn clear(&mut self) {
+        *self = default();
+    }
+}
+
+/// An `EXTERNAL` function bound with [InkStories::bind_async_external].
+pub(crate) struct AsyncExternal(pub(crate) Rc<RefCell<AsyncCalls>>);
+
+impl ExternalFunction for AsyncExternal {
+    fn call(&mut self, name: &str, args: Vec<ValueType>) -> Option<ValueType> {
+        let mut calls = self.0.borrow_mut();
+        if let Some(value) = calls.resolved.get(calls.used) {
+            let value = value.to_value_type();
+            calls.used += 1;
+            return value;
+        }
+        if calls.unresolved.is_none() {
+            calls.unresolved = Some(PendingExternal {
+                name: name.to_string(),
+                args: args
+                    .iter()
+                    .filter_map(VariableValue::from_value_type)
+                    .collect(),
+            });
+        }
+        // The line is played again once the call is resolved, so this value is
+        // thrown away. It's one rather than nothing so the ink can't fail on it
+        // meanwhile, e.g., dividing by it.
+        Some(ValueType::from(1))
+    }
+}
+
+/// Where to play a line again from if it calls an unresolved async external.
+pub(crate) struct Replay {
+    state: String,
+    events: usize,
+}
+
+impl InkEntry {
+    /// Whether the story waits on an async external.
+    pub(crate) fn awaits_external(&self) -> bool {
+        self.async_calls
+            .as_ref()
+            .is_some_and(|calls| calls.borrow().awaiting.is_some())
+    }
+
+    /// Errors if the story waits on an async external; otherwise returns
+    /// where to play the coming line again from, if it has any.
+    pub(crate) fn begin_async(&mut self) -> Result<Option<Replay>, InkError> {
+        let Some(calls) = &self.async_calls else {
+            return Ok(None);
+        };
+        let mut calls = calls.borrow_mut();
+        if let Some(pending) = &calls.awaiting {
+            return Err(InkError::ExternalPending(pending.name.clone()));
+        }
+        calls.used = 0;
+        Ok(Some(Replay {
+            state: self.story.save_state()?,
+            events: self.story_events.borrow().len(),
+        }))
+    }
+
+    /// Puts the story back as it was before the line if the line called an
+    /// unresolved async external, and returns that call.
+    pub(crate) fn end_async(
+        &mut self,
+        replay: Option<Replay>,
+    ) -> Result<Option<PendingExternal>, InkError> {
+        let (Some(calls), Some(replay)) = (&self.async_calls, replay) else {
+            return Ok(None);
+        };
+        let mut calls = calls.borrow_mut();
+        let Some(pending) = calls.unresolved.take() else {
+            calls.resolved.clear();
+            return Ok(None);
+        };
+        calls.awaiting = Some(pending.clone());
+        self.story.load_state(&replay.state)?;
+        self.story_events.borrow_mut().truncate(replay.events);
+        self.errors.take();
+        Ok(Some(pending))
+    }
+}
+
+impl InkStories {
+    /// Binds the `EXTERNAL` function `name` to wait for the game, e.g., on a
+    /// network lookup, instead of returning a value right away. When a line
+    /// calls it, [InkStories::cont] puts the story back as it was before the
+    /// line, raises [InkEvent::ExternalPending], and errors with
+    /// [InkError::ExternalPending], as it does until the call is given its
+    /// value with [InkStories::resolve_external]. The next continue then plays
+    /// the line again, with that value.
+    ///
+    /// Playing a line again calls the line's other externals again too, so
+    /// keep those free of side effects. Lists and divert targets can't be
+    /// returned.
+    pub fn bind_async_external(&mut self, id: Entity, name: &str) -> Result<(), InkError> {
+        let entry = self.entry_mut(id)?;
+        if entry.current_line.is_some() {
+            return Err(InkError::AlreadyStarted);
+        }
+        entry.bind_own(name, StoryExternal::Async)?;
+        Ok(())
+    }
+
+    /// Gives the async external call the story waits on its `value`, so the
+    /// next [InkStories::cont] carries on. Errors with
+    /// [InkError::NoPendingExternal] if it isn't waiting.
+    pub fn resolve_external(&mut self, id: Entity, value: VariableValue) -> Result<(), InkError> {
+        let entry = self.entry_mut(id)?;
+        let mut calls = entry
+            .async_calls
+            .as_ref()
+            .ok_or(InkError::NoPendingExternal)?
+            .borrow_mut();
+        calls.awaiting.take().ok_or(InkError::NoPendingExternal)?;
+        calls.resolved.push(value);
+        Ok(())
+    }
+
+    /// Whether the story has text to continue now: it hasn't reached its
+    /// choices or end and doesn't wait on an async external. [Story]'s own
+    /// `can_continue` stays true while it waits, as the line is played again.
+    pub fn can_continue(&self, id: Entity) -> Result<bool, InkError> {
+        let entry = self.entry(id)?;
+        Ok(entry.story.can_continue() && !entry.awaits_external())
+    }
+
+    /// Returns the async external call the story waits on, if any.
+    pub fn pending_external(&self, id: Entity) -> Result<Option<PendingExternal>, InkError> {
+        Ok(self
+            .entry(id)?
+            .async_calls
+            .as_ref()
+            .and_then(|calls| calls.borrow().awaiting.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROLL: &str = include_str!("../assets/roll.ink.json");
+
+    #[test]
+    fn a_story_waiting_on_an_external_cannot_continue() {
+        let mut ink_stories = InkStories::default();
+        let id = Entity::from_raw(0);
+        ink_stories.parse_from_str(id, ROLL).unwrap();
+        ink_stories.bind_async_external(id, "roll").unwrap();
+        assert!(ink_stories.can_continue(id).unwrap());
+        assert!(ink_stories.cont(id).is_err());
+
+        assert!(!ink_stories.can_continue(id).unwrap());
+        assert!(ink_stories.step_all_continuable().is_empty());
+        assert!(ink_stories.cont_n(id, 3).unwrap().is_empty());
+
+        ink_stories
+            .resolve_external(id, VariableValue::Int(2))
+            .unwrap();
+        assert!(ink_stories.can_continue(id).unwrap());
+        assert_eq!(ink_stories.cont(id).unwrap(), "You rolled 2.\n");
+    }
+}
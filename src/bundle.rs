@@ -0,0 +1,61 @@
+use super::*;
+
+/// A story packaged for sharing: its compiled JSON, what it was compiled
+/// from and with, its global tags, and the state it was exported in. With
+/// the `serde` feature it can be serialized, e.g., to a mod's file.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct InkBundle {
+    /// The compiled `.ink.json`.
+    pub json: String,
+    /// Whether the JSON was loaded as is or compiled from `.ink` source.
+    pub format: InkFormat,
+    /// The `inkVersion` the JSON was compiled with.
+    pub ink_version: Option<i32>,
+    pub global_tags: Vec<String>,
+    /// The story's saved state.
+    pub state: String,
+}
+
+impl InkStories {
+    /// Packages the story and its current state into a bundle.
+    pub fn export_bundle(&self, id: Entity) -> Result<InkBundle, InkError> {
+        let entry = self.entry(id)?;
+        Ok(InkBundle {
+            json: entry.json.to_string(),
+            format: entry.format,
+            ink_version: compiled::ink_version(&entry.json),
+            global_tags: entry.story.get_global_tags()?,
+            state: entry.story.save_state()?,
+        })
+    }
+
+    /// Parses the bundle's story for `id` and restores its state. The story
+    /// keeps the settings its prior story had, as a reload does. Returns the
+    /// prior story if there was one.
+    ///
+    /// This doesn't give the entity [InkStory]; see [import_ink_bundle].
+    pub fn import_bundle(
+        &mut self,
+        id: Entity,
+        bundle: &InkBundle,
+    ) -> Result<Option<Story>, InkError> {
+        let mut story = Story::new(&bundle.json)?;
+        story.load_state(&bundle.state)?;
+        Ok(self.insert(id, story, bundle.json.as_str().into(), bundle.format))
+    }
+}
+
+/// Imports `bundle` as the story of `id` like [InkStories::import_bundle],
+/// giving it [InkStory] and raising [InkEvent::OnStoryLoaded].
+pub fn import_ink_bundle(
+    world: &mut World,
+    id: Entity,
+    bundle: &InkBundle,
+) -> Result<(), InkError> {
+    world
+        .non_send_resource_mut::<InkStories>()
+        .import_bundle(id, bundle)?;
+    mark_loaded(world, id);
+    Ok(())
+}
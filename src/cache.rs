@@ -0,0 +1,37 @@
+//! Skips compiling `.ink` source that has an up-to-date `.ink.json` beside it.
+use super::*;
+use bevy::asset::io::file::FileAssetReader;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+}
+
+/// Returns the JSON of `source`'s sibling `.ink.json` if it was modified
+/// after the source and every file it includes, as returned by
+/// [include::read_includes]. `root` is the directory on disk the asset paths
+/// are relative to; relative roots resolve like Bevy's own file assets.
+pub(crate) fn fresh_json(
+    root: &Path,
+    source: &Path,
+    includes: &[(PathBuf, Vec<u8>)],
+) -> Option<String> {
+    let root = FileAssetReader::get_base_path().join(root);
+    let source = root.join(source);
+    let mut json = source.clone().into_os_string();
+    json.push(".json");
+    let json = PathBuf::from(json);
+    let compiled = modified(&json)?;
+    let dir = source.parent()?;
+    let sources =
+        std::iter::once(source.clone()).chain(includes.iter().map(|(path, _)| dir.join(path)));
+    for path in sources {
+        if modified(&path)? > compiled {
+            return None;
+        }
+    }
+    std::fs::read_to_string(json).ok()
+}
@@ -0,0 +1,97 @@
+use super::*;
+use std::sync::mpsc::{self, Receiver, Sender};
+
+/// A story operation sent through an [InkSender] for the thread that owns
+/// [InkStories] to carry out.
+#[derive(Debug, Clone)]
+pub enum InkCommand {
+    /// [InkStories::cont], replied to with [InkReply::Line].
+    Continue(Entity),
+    /// [InkStories::choose_choice_index], replied to with [InkReply::Done].
+    Choose(Entity, usize),
+    /// [InkStories::current_choices], replied to with [InkReply::Choices].
+    Choices(Entity),
+    /// [InkStories::save_state], replied to with [InkReply::State].
+    SaveState(Entity),
+    /// [InkStories::load_state], replied to with [InkReply::Done].
+    LoadState(Entity, String),
+}
+
+/// What an [InkCommand] returned.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InkReply {
+    Line(String),
+    Choices(Vec<ChoiceInfo>),
+    State(String),
+    Done,
+}
+
+type InkRequest = (InkCommand, Sender<Result<InkReply, InkError>>);
+
+/// Sends [InkCommand]s from any thread, e.g., a server's networking tasks,
+/// to be carried out by [InkStories::process_commands] on the thread that
+/// owns the stories. Clone it for each sender.
+#[derive(Debug, Clone)]
+pub struct InkSender(Sender<InkRequest>);
+
+impl InkSender {
+    /// Queues `command` and returns where its reply will arrive. The reply is
+    /// an error if the story couldn't carry it out; the receiver disconnects
+    /// without one if the [InkReceiver] is dropped first.
+    pub fn send(&self, command: InkCommand) -> Receiver<Result<InkReply, InkError>> {
+        let (reply, receiver) = mpsc::channel();
+        // A disconnected InkReceiver drops the reply sender, which the caller
+        // sees as a disconnected receiver.
+        let _ = self.0.send((command, reply));
+        receiver
+    }
+}
+
+/// Receives what [InkSender]s send. Insert it as a resource to have
+/// [process_ink_commands] carry out the commands each frame; without an
+/// `App`, pass it to [InkStories::process_commands] instead.
+#[derive(Debug, Resource)]
+pub struct InkReceiver(Mutex<Receiver<InkRequest>>);
+
+/// Returns a connected [InkSender] and [InkReceiver].
+pub fn ink_channel() -> (InkSender, InkReceiver) {
+    let (sender, receiver) = mpsc::channel();
+    (InkSender(sender), InkReceiver(Mutex::new(receiver)))
+}
+
+impl InkStories {
+    /// Carries out every command waiting in `receiver`, in the order sent,
+    /// and replies to each. Returns how many there were.
+    pub fn process_commands(&mut self, receiver: &InkReceiver) -> usize {
+        let receiver = receiver.0.lock().unwrap_or_else(|e| e.into_inner());
+        let mut count = 0;
+        for (command, reply) in receiver.try_iter() {
+            let result = match command {
+                InkCommand::Continue(id) => self.cont(id).map(InkReply::Line),
+                InkCommand::Choose(id, index) => {
+                    self.choose_choice_index(id, index).map(|_| InkReply::Done)
+                }
+                InkCommand::Choices(id) => self.current_choices(id).map(InkReply::Choices),
+                InkCommand::SaveState(id) => self.save_state(id).map(InkReply::State),
+                InkCommand::LoadState(id, state) => {
+                    self.load_state(id, &state).map(|_| InkReply::Done)
+                }
+            };
+            // The sender may have stopped waiting for the reply.
+            let _ = reply.send(result);
+            count += 1;
+        }
+        count
+    }
+}
+
+/// Carries out the commands sent to the [InkReceiver] resource, if there is
+/// one.
+pub fn process_ink_commands(
+    receiver: Option<Res<InkReceiver>>,
+    mut ink_stories: NonSendMut<InkStories>,
+) {
+    if let Some(receiver) = receiver {
+        ink_stories.process_commands(&receiver);
+    }
+}
@@ -0,0 +1,513 @@
+use super::*;
+use bladeink::choice::Choice;
+
+/// A choice offered by a story.
+///
+/// Fallback choices (`* ->`) never show up as these: bladeink leaves them out
+/// of the current choices and takes one by itself once it's the only choice
+/// left, so every choice here can be shown to the player.
+///
+/// Bladeink doesn't say where a choice leads or whether it's sticky; see
+/// [InkStories::choice_paths] and [InkStories::choice_once_only] for that.
+#[derive(Debug, Clone, PartialEq, Reflect)]
+pub struct ChoiceInfo {
+    /// The index to choose this choice with, among the choices a
+    /// [ChoiceFilter] lets through.
+    pub index: usize,
+    /// The index bladeink knows this choice by, among all the story's current
+    /// choices. [InkStories::choose] chooses by it, so there's no mapping
+    /// between the two to get wrong.
+    pub story_index: usize,
+    pub text: String,
+    pub tags: Vec<String>,
+}
+
+impl From<&Choice> for ChoiceInfo {
+    fn from(choice: &Choice) -> Self {
+        Self {
+            index: *choice.index.borrow(),
+            story_index: *choice.index.borrow(),
+            text: choice.text.clone(),
+            tags: choice.tags.clone(),
+        }
+    }
+}
+
+/// Everything one [InkStories::step] produces.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StepResult {
+    pub text: String,
+    pub tags: Vec<String>,
+    /// The choices offered after this line, empty unless the story stopped.
+    pub choices: Vec<ChoiceInfo>,
+}
+
+/// Where a story is between lines, from [InkStories::state], e.g., for a UI
+/// to choose between showing text, a menu, or the end.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InkStoryState {
+    /// The story has text to continue before any choices.
+    Continuable,
+    /// The story waits on these choices.
+    AtChoice(Vec<ChoiceInfo>),
+    /// The story can't continue and offers no choices.
+    Ended,
+    /// The story waits on an async external call; see
+    /// [InkStories::bind_async_external].
+    AwaitingExternal(PendingExternal),
+}
+
+/// Insert on a story entity to choose its current choice at this index, e.g.,
+/// from an input layer without access to [InkStories]. [select_choices]
+/// chooses and removes it, raising [InkEvent::ChoiceError] if it can't.
+#[derive(Debug, Component, Clone, Copy)]
+pub struct SelectChoice(pub usize);
+
+/// Chooses each loaded story's [SelectChoice].
+pub fn select_choices(
+    mut ink_stories: NonSendMut<InkStories>,
+    query: Query<(Entity, &SelectChoice), With<InkStory>>,
+    mut commands: Commands,
+) {
+    for (id, &SelectChoice(index)) in &query {
+        if let Err(err) = ink_stories.choose_choice_index(id, index) {
+            ink_stories.events.push(InkEvent::ChoiceError {
+                entity: id,
+                index,
+                message: err.to_string(),
+            });
+        }
+        commands.entity(id).remove::<SelectChoice>();
+    }
+}
+
+/// Add to a story entity to hide the choices this returns `false` for, e.g.,
+/// `# debug` choices outside dev builds. Hidden choices are left out of
+/// [InkStories::current_choices] and the like, and choice indices count only
+/// the shown ones, so [InkStories::choose_choice_index] still picks the choice
+/// shown at that index. Kept across hot reloads.
+#[derive(Component, Clone)]
+pub struct ChoiceFilter(pub ChoicePredicate);
+
+/// Whether a choice is shown; see [ChoiceFilter].
+pub type ChoicePredicate = Arc<dyn Fn(&ChoiceInfo) -> bool + Send + Sync>;
+
+impl ChoiceFilter {
+    pub fn new(shown: impl Fn(&ChoiceInfo) -> bool + Send + Sync + 'static) -> Self {
+        Self(Arc::new(shown))
+    }
+}
+
+impl std::fmt::Debug for ChoiceFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("ChoiceFilter").finish_non_exhaustive()
+    }
+}
+
+/// Hands each story its [ChoiceFilter], or takes it back once removed.
+pub fn sync_choice_filters(
+    mut ink_stories: NonSendMut<InkStories>,
+    query: Query<(Entity, Ref<ChoiceFilter>, Ref<InkStory>)>,
+    mut removed: RemovedComponents<ChoiceFilter>,
+) {
+    for id in removed.read() {
+        if let Ok(entry) = ink_stories.entry_mut(id) {
+            entry.choice_filter = None;
+        }
+    }
+    for (id, filter, story) in &query {
+        if !filter.is_changed() && !story.is_added() {
+            continue;
+        }
+        if let Ok(entry) = ink_stories.entry_mut(id) {
+            entry.choice_filter = Some(filter.0.clone());
+        }
+    }
+}
+
+/// Add to a story entity to keep an [InkCurrentChoices] with its choices.
+#[derive(Debug, Component, Clone)]
+pub struct TrackChoices;
+
+/// The texts of the current choices of a [TrackChoices] story, in choice
+/// index order. Updated on [InkEvent::OnChoicesChanged], so change detection
+/// on it tells when to rebuild a choice UI.
+#[derive(Debug, Component, Clone, Default)]
+pub struct InkCurrentChoices(pub Vec<String>);
+
+/// Keeps [InkCurrentChoices] up to date.
+pub fn track_choices(
+    mut events: EventReader<InkEvent>,
+    tracked: Query<(), With<TrackChoices>>,
+    mut commands: Commands,
+) {
+    for ev in events.read() {
+        if let InkEvent::OnChoicesChanged { entity, choices } = ev
+            && tracked.contains(*entity)
+        {
+            let texts = choices.iter().map(|choice| choice.text.clone()).collect();
+            commands.entity(*entity).insert(InkCurrentChoices(texts));
+        }
+    }
+}
+
+/// Add to a story entity to keep a highlighted choice for gamepad or keyboard
+/// menus: the index among its current choices. [sync_choice_cursors] puts it
+/// back on the first choice at each new choice point and keeps it within the
+/// choices on offer.
+#[derive(Debug, Component, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InkChoiceCursor(pub usize);
+
+impl InkChoiceCursor {
+    /// Moves to the choice above, staying on the first.
+    pub fn up(&mut self) {
+        self.0 = self.0.saturating_sub(1);
+    }
+
+    /// Moves to the choice below. [sync_choice_cursors] keeps it on the last.
+    pub fn down(&mut self) {
+        self.0 += 1;
+    }
+
+    /// Chooses the highlighted choice like [InkStories::choose_choice_index].
+    pub fn confirm(&self, ink_stories: &mut InkStories, id: Entity) -> Result<(), InkError> {
+        ink_stories.choose_choice_index(id, self.0)
+    }
+}
+
+/// Resets each [InkChoiceCursor] at a new choice point and keeps it within
+/// its story's choices.
+pub fn sync_choice_cursors(
+    ink_stories: NonSend<InkStories>,
+    mut events: EventReader<InkEvent>,
+    mut cursors: Query<(Entity, &mut InkChoiceCursor)>,
+) {
+    for ev in events.read() {
+        match ev {
+            InkEvent::OnChoicePoint { entity, .. } => {
+                if let Ok((_, mut cursor)) = cursors.get_mut(*entity) {
+                    cursor.set_if_neq(InkChoiceCursor(0));
+                }
+            }
+            InkEvent::OnChoicesChanged { entity, choices } => {
+                if let Ok((_, mut cursor)) = cursors.get_mut(*entity) {
+                    let last = choices.len().saturating_sub(1);
+                    cursor.set_if_neq(InkChoiceCursor(cursor.0.min(last)));
+                }
+            }
+            _ => {}
+        }
+    }
+    for (id, mut cursor) in &mut cursors {
+        if !cursor.is_changed() {
+            continue;
+        }
+        if let Ok(count) = ink_stories.choice_count(id) {
+            let last = count.saturating_sub(1);
+            cursor.set_if_neq(InkChoiceCursor(cursor.0.min(last)));
+        }
+    }
+}
+
+/// Continues `story` as far as it goes and returns the choices it stops at,
+/// giving up after `max_continues` lines like [InkStories::cont_maximally].
+fn choices_after_continuing(
+    story: &mut Story,
+    max_continues: usize,
+) -> Result<Vec<ChoiceInfo>, InkError> {
+    let mut count = 0;
+    while story.can_continue() {
+        if count >= max_continues {
+            return Err(InkError::ContinueLimitExceeded(count));
+        }
+        story.cont()?;
+        count += 1;
+    }
+    Ok(story
+        .get_current_choices()
+        .iter()
+        .map(|choice| ChoiceInfo::from(choice.as_ref()))
+        .collect())
+}
+
+impl InkStories {
+    /// Chooses the current choice at `index`, erroring with
+    /// [InkError::ChoiceIndexOutOfRange] if there's no such choice, or with
+    /// [InkError::NotAtChoicePoint] if the story can still continue.
+    pub fn choose_choice_index(&mut self, id: Entity, index: usize) -> Result<(), InkError> {
+        if self.get(id)?.can_continue() {
+            return Err(InkError::NotAtChoicePoint);
+        }
+        let choices = self.visible_choices(id)?;
+        let Some(choice) = choices.get(index) else {
+            return Err(InkError::ChoiceIndexOutOfRange {
+                index,
+                available: choices.len(),
+            });
+        };
+        let story_index = choice.story_index;
+        self.take_choice(id, story_index)
+    }
+
+    /// Chooses `choice`, one of the story's current choices, by its
+    /// [ChoiceInfo::story_index], e.g., a choice from
+    /// [InkStories::choices_with_tag]. Errors with [InkError::NoSuchChoice] if
+    /// it's no longer on offer or a [ChoiceFilter] hides it.
+    pub fn choose(&mut self, id: Entity, choice: &ChoiceInfo) -> Result<(), InkError> {
+        if self.get(id)?.can_continue() {
+            return Err(InkError::NotAtChoicePoint);
+        }
+        let offered = self.visible_choices(id)?.iter().any(|current| {
+            current.story_index == choice.story_index && current.text == choice.text
+        });
+        if !offered {
+            return Err(InkError::NoSuchChoice(choice.text.clone()));
+        }
+        self.take_choice(id, choice.story_index)
+    }
+
+    /// Chooses the choice bladeink knows by `story_index`.
+    fn take_choice(&mut self, id: Entity, story_index: usize) -> Result<(), InkError> {
+        let _span = info_span!("ink_choose", entity = %id, story_index).entered();
+        let entry = self.entry_mut(id)?;
+        entry.snapshot()?;
+        entry.story.choose_choice_index(story_index)?;
+        entry.buffered_text.clear();
+        self.auto_save(id)?;
+        self.raise_choices_changed(id);
+        Ok(())
+    }
+
+    /// Chooses the current choice at `index` like
+    /// [InkStories::choose_choice_index], then continues one line like
+    /// [InkStories::cont] and returns it.
+    pub fn choose_and_continue(&mut self, id: Entity, index: usize) -> Result<String, InkError> {
+        self.choose_choice_index(id, index)?;
+        self.cont(id)
+    }
+
+    /// Raises [InkEvent::OnChoicesChanged] if the current choices aren't the
+    /// ones last raised.
+    pub(crate) fn raise_choices_changed(&mut self, id: Entity) {
+        let Ok(choices) = self.current_choices(id) else {
+            return;
+        };
+        let Ok(entry) = self.entry_mut(id) else {
+            return;
+        };
+        if entry.choices != choices {
+            entry.choices = choices.clone();
+            self.events.push(InkEvent::OnChoicesChanged {
+                entity: id,
+                choices,
+            });
+        }
+    }
+
+    /// Raises [InkEvent::OnChoicePoint] if the story stopped for choices.
+    pub(crate) fn raise_choice_point(&mut self, id: Entity) {
+        if self.get(id).map_or(true, |story| story.can_continue()) {
+            return;
+        }
+        let Ok(choices) = self.current_choices(id) else {
+            return;
+        };
+        if !choices.is_empty() {
+            self.events.push(InkEvent::OnChoicePoint {
+                entity: id,
+                choices,
+            });
+        }
+    }
+
+    /// Continue the story one line like [InkStories::cont] and return the
+    /// line with its tags and the choices then on offer, all in one call.
+    pub fn step(&mut self, id: Entity) -> Result<StepResult, InkError> {
+        let text = self.cont(id)?;
+        Ok(StepResult {
+            text,
+            tags: self.current_tags(id)?,
+            choices: self.current_choices(id)?,
+        })
+    }
+
+    /// Returns whether the story can continue, waits on choices, or has
+    /// ended, in one call.
+    pub fn state(&self, id: Entity) -> Result<InkStoryState, InkError> {
+        if let Some(pending) = self.pending_external(id)? {
+            return Ok(InkStoryState::AwaitingExternal(pending));
+        }
+        if self.get(id)?.can_continue() {
+            return Ok(InkStoryState::Continuable);
+        }
+        let choices = self.current_choices(id)?;
+        Ok(if choices.is_empty() {
+            InkStoryState::Ended
+        } else {
+            InkStoryState::AtChoice(choices)
+        })
+    }
+
+    /// Returns the current choices its [ChoiceFilter] lets through, indexed
+    /// among them.
+    pub(crate) fn visible_choices(&self, id: Entity) -> Result<Vec<ChoiceInfo>, InkError> {
+        let entry = self.entry(id)?;
+        Ok(entry
+            .story
+            .get_current_choices()
+            .iter()
+            .map(|choice| ChoiceInfo::from(choice.as_ref()))
+            .filter(|choice| {
+                entry
+                    .choice_filter
+                    .as_ref()
+                    .is_none_or(|shown| shown(choice))
+            })
+            .enumerate()
+            .map(|(index, mut choice)| {
+                choice.index = index;
+                choice
+            })
+            .collect())
+    }
+
+    /// Returns the choices the story offers now.
+    pub fn current_choices(&self, id: Entity) -> Result<Vec<ChoiceInfo>, InkError> {
+        self.visible_choices(id)
+    }
+
+    /// Returns a hash of the choices the story offers now, their text, tags,
+    /// and indices, e.g., for a UI to skip rebuilding a menu that hasn't
+    /// changed without keeping the choices to compare. The hash is the same
+    /// for the same choices across runs of the same build.
+    pub fn choices_hash(&self, id: Entity) -> Result<u64, InkError> {
+        use std::hash::{DefaultHasher, Hash, Hasher};
+        let mut hasher = DefaultHasher::new();
+        for choice in self.visible_choices(id)? {
+            choice.index.hash(&mut hasher);
+            choice.story_index.hash(&mut hasher);
+            choice.text.hash(&mut hasher);
+            choice.tags.hash(&mut hasher);
+        }
+        Ok(hasher.finish())
+    }
+
+    /// Returns how many choices the story offers now, without copying them
+    /// unless a [ChoiceFilter] has to look at them.
+    pub fn choice_count(&self, id: Entity) -> Result<usize, InkError> {
+        let entry = self.entry(id)?;
+        if entry.choice_filter.is_some() {
+            return Ok(self.visible_choices(id)?.len());
+        }
+        Ok(entry.story.get_current_choices().len())
+    }
+
+    /// Returns the text of the current choice at `index`, erroring with
+    /// [InkError::ChoiceIndexOutOfRange] if there's no such choice.
+    pub fn choice_text(&self, id: Entity, index: usize) -> Result<String, InkError> {
+        let mut choices = self.visible_choices(id)?;
+        let available = choices.len();
+        if index >= available {
+            return Err(InkError::ChoiceIndexOutOfRange { index, available });
+        }
+        Ok(choices.swap_remove(index).text)
+    }
+
+    /// Returns the current choices tagged `tag`, e.g., `"combat"` for
+    /// `* [Attack] # combat`. They keep their indices for
+    /// [InkStories::choose_choice_index].
+    pub fn choices_with_tag(&self, id: Entity, tag: &str) -> Result<Vec<ChoiceInfo>, InkError> {
+        let mut choices = self.current_choices(id)?;
+        choices.retain(|choice| choice.tags.iter().any(|t| t.trim() == tag));
+        Ok(choices)
+    }
+
+    /// Chooses the current choice whose text is `text`. If several share that
+    /// text the first is chosen.
+    pub fn choose_choice_by_text(&mut self, id: Entity, text: &str) -> Result<(), InkError> {
+        let index = self
+            .current_choices(id)?
+            .iter()
+            .position(|choice| choice.text == text)
+            .ok_or_else(|| InkError::NoSuchChoice(text.to_string()))?;
+        self.choose_choice_index(id, index)
+    }
+
+    /// Returns the choices the story would offer if it jumped to `path` now
+    /// and continued until it reached them.
+    ///
+    /// This runs on a copy of the story, so the story itself is untouched. The
+    /// copy starts from the story's current state, so its variables and visit
+    /// counts decide which conditional choices appear.
+    pub fn choices_at_path(&self, id: Entity, path: &str) -> Result<Vec<ChoiceInfo>, InkError> {
+        let mut story = self.fork(id)?;
+        story.choose_path_string(path, true, None)?;
+        choices_after_continuing(&mut story, self.max_continues)
+    }
+
+    /// Returns the choices offered at an entry point such as a chapter's knot,
+    /// e.g., for a scene-selection menu.
+    ///
+    /// Unlike [InkStories::choices_at_path] this starts a fresh copy of the
+    /// story as though nothing had been played yet, so the result doesn't
+    /// depend on the current playthrough. The story itself is untouched.
+    pub fn entry_choices(&self, id: Entity, entry_path: &str) -> Result<Vec<ChoiceInfo>, InkError> {
+        let mut story = Story::new(&self.entry(id)?.json)?;
+        story.choose_path_string(entry_path, true, None)?;
+        choices_after_continuing(&mut story, self.max_continues)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHOICES: &str = include_str!("../assets/choices.ink.json");
+
+    #[test]
+    fn choosing_before_the_choices_errors() {
+        let mut ink_stories = InkStories::default();
+        let id = Entity::from_raw(0);
+        ink_stories.parse_from_str(id, CHOICES).unwrap();
+        assert_eq!(ink_stories.cont(id).unwrap(), "Hello.\n");
+        assert!(matches!(
+            ink_stories.choose_choice_index(id, 0),
+            Err(InkError::NotAtChoicePoint)
+        ));
+
+        assert_eq!(ink_stories.cont(id).unwrap(), "How are you?\n");
+        ink_stories.choose_choice_index(id, 0).unwrap();
+        assert_eq!(ink_stories.cont(id).unwrap(), "Glad to hear.\n");
+    }
+
+    #[test]
+    fn a_filtered_choice_is_chosen_by_its_story_index() {
+        let mut app = crate::tests::app();
+        let handle = app
+            .world_mut()
+            .resource_mut::<Assets<InkText>>()
+            .add(InkText(CHOICES.into(), InkFormat::Json));
+        let id = app
+            .world_mut()
+            .spawn((
+                InkLoad(handle),
+                ChoiceFilter::new(|choice| choice.text != "Fine"),
+            ))
+            .id();
+        app.update();
+        app.update();
+
+        let mut ink_stories = app.world_mut().non_send_resource_mut::<InkStories>();
+        ink_stories.cont(id).unwrap();
+        ink_stories.cont(id).unwrap();
+        // The fallback is never offered, and the filter hides "Fine".
+        assert_eq!(ink_stories.get(id).unwrap().get_current_choices().len(), 2);
+        let choices = ink_stories.current_choices(id).unwrap();
+        let [tired] = choices.as_slice() else {
+            panic!("unexpected choices {choices:?}");
+        };
+        assert_eq!((tired.index, tired.story_index), (0, 1));
+        ink_stories.choose(id, tired).unwrap();
+        assert_eq!(ink_stories.cont(id).unwrap(), "Get some rest.\n");
+    }
+}
@@ -0,0 +1,148 @@
+//! Reads what bladeink doesn't expose out of a story's compiled JSON.
+use serde_json::{Map, Value};
+
+fn parse(json: &str) -> Option<Value> {
+    serde_json::from_str(json).ok()
+}
+
+/// The named content of the root container, i.e., knots and `global decl`.
+fn root_named_content(story: &Value) -> Option<&Map<String, Value>> {
+    story.get("root")?.as_array()?.last()?.as_object()
+}
+
+/// Returns the global variable names in declaration order.
+pub(crate) fn global_variable_names(json: &str) -> Vec<String> {
+    let Some(story) = parse(json) else {
+        return Vec::new();
+    };
+    root_named_content(&story)
+        .and_then(|named| named.get("global decl"))
+        .and_then(Value::as_array)
+        .map(|decl| {
+            decl.iter()
+                .filter_map(|obj| {
+                    let obj = obj.as_object()?;
+                    // "re" marks a reassignment rather than a declaration.
+                    if obj.contains_key("re") {
+                        return None;
+                    }
+                    obj.get("VAR=")?.as_str().map(String::from)
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Returns the `inkVersion` the story was compiled with.
+pub(crate) fn ink_version(json: &str) -> Option<i32> {
+    parse(json)?
+        .get("inkVersion")?
+        .as_i64()
+        .and_then(|version| i32::try_from(version).ok())
+}
+
+/// Returns the names of the `EXTERNAL` functions the story calls, sorted.
+pub(crate) fn external_names(json: &str) -> Vec<String> {
+    fn collect(value: &Value, names: &mut Vec<String>) {
+        match value {
+            Value::Array(items) => items.iter().for_each(|item| collect(item, names)),
+            Value::Object(obj) => {
+                if let Some(name) = obj.get("x()").and_then(Value::as_str) {
+                    names.push(name.to_string());
+                }
+                obj.values().for_each(|value| collect(value, names));
+            }
+            _ => {}
+        }
+    }
+    let Some(story) = parse(json) else {
+        return Vec::new();
+    };
+    let mut names = Vec::new();
+    if let Some(root) = story.get("root") {
+        collect(root, &mut names);
+    }
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Returns the names of the `EXTERNAL` functions the story calls, sorted,
+/// that have no ink function of the same name to fall back on.
+pub(crate) fn unbacked_external_names(json: &str) -> Vec<String> {
+    let mut names = external_names(json);
+    let knots = knot_names(json);
+    names.retain(|name| !knots.contains(name));
+    names
+}
+
+/// Returns the content at an absolute runtime `path`, e.g., `"knot.0.3"`.
+fn resolve<'a>(root: &'a Value, path: &str) -> Option<&'a Value> {
+    let mut node = root;
+    for component in path.split('.') {
+        let items = node.as_array()?;
+        node = match component.parse::<usize>() {
+            Ok(index) => items.get(index)?,
+            // Named containers are kept in the trailing object, or in the
+            // content itself with their name under `#n`.
+            Err(_) => items
+                .last()
+                .and_then(Value::as_object)
+                .and_then(|named| named.get(component))
+                .or_else(|| {
+                    items.iter().find(|item| {
+                        item.as_array()
+                            .and_then(|content| content.last()?.get("#n")?.as_str())
+                            == Some(component)
+                    })
+                })?,
+        };
+    }
+    Some(node)
+}
+
+/// The choice point flag for once-only choices, i.e., `*` rather than `+`.
+const ONCE_ONLY: u64 = 0x10;
+
+/// Returns whether each choice point at `paths` is once-only, or `None` for a
+/// path that isn't a choice point.
+pub(crate) fn choice_points_once_only(json: &str, paths: &[&str]) -> Vec<Option<bool>> {
+    let story = parse(json);
+    let root = story.as_ref().and_then(|story| story.get("root"));
+    paths
+        .iter()
+        .map(|path| {
+            let point = resolve(root?, path)?.as_object()?;
+            point.get("*")?;
+            let flags = point.get("flg").and_then(Value::as_u64).unwrap_or(0);
+            Some(flags & ONCE_ONLY != 0)
+        })
+        .collect()
+}
+
+/// Returns the names of the top-level knots, including ink functions, sorted.
+pub(crate) fn knot_names(json: &str) -> Vec<String> {
+    let Some(story) = parse(json) else {
+        return Vec::new();
+    };
+    root_named_content(&story)
+        .map(|named| {
+            named
+                .keys()
+                // `#f` and `#n` are the container's own flags and name.
+                .filter(|name| *name != "global decl" && !name.starts_with('#'))
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Rewrites the story as compact JSON with only what the runtime reads:
+/// `inkVersion`, `root`, and `listDefs`. Returns `None` if it isn't JSON.
+pub(crate) fn strip_debug(json: &str) -> Option<String> {
+    let Value::Object(mut story) = parse(json.trim_start_matches('\u{feff}'))? else {
+        return None;
+    };
+    story.retain(|key, _| matches!(key.as_str(), "inkVersion" | "root" | "listDefs"));
+    serde_json::to_string(&Value::Object(story)).ok()
+}
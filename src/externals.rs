@@ -0,0 +1,391 @@
+use super::*;
+use bladeink::{story::external_functions::ExternalFunction, value_type::ValueType};
+
+/// Add to a story entity to have it raise [InkEvent::ExternalCall] when it
+/// calls an `EXTERNAL` function that isn't bound. See
+/// [InkStories::forward_external_calls].
+#[derive(Debug, Component, Clone)]
+pub struct ForwardExternalCalls;
+
+/// Queues each call as an [InkEvent::ExternalCall] for [InkStories::cont] to
+/// raise.
+struct Forwarder {
+    entity: Entity,
+    calls: Rc<RefCell<Vec<InkEvent>>>,
+}
+
+impl ExternalFunction for Forwarder {
+    fn call(&mut self, name: &str, args: Vec<ValueType>) -> Option<ValueType> {
+        self.calls.borrow_mut().push(InkEvent::ExternalCall {
+            entity: self.entity,
+            name: name.to_string(),
+            args: args
+                .iter()
+                .filter_map(VariableValue::from_value_type)
+                .collect(),
+        });
+        None
+    }
+}
+
+/// A Rust function bound with [InkStories::bind_external_function].
+struct RustFunction(GlobalExternal);
+
+impl ExternalFunction for RustFunction {
+    fn call(&mut self, name: &str, args: Vec<ValueType>) -> Option<ValueType> {
+        let args = args
+            .iter()
+            .filter_map(VariableValue::from_value_type)
+            .collect();
+        let value = (self.0)(args);
+        let result = value.to_value_type();
+        if result.is_none() {
+            warn!("External function {name:?} returned {value:?}, which ink can't take back");
+        }
+        result
+    }
+}
+
+/// A function bound in every story with [InkStories::bind_global_external].
+pub(crate) type GlobalExternal = Rc<dyn Fn(Vec<VariableValue>) -> VariableValue>;
+
+/// Sends a Bevy event made by an `EXTERNAL` call; see
+/// [InkStories::bind_external_to_event].
+pub(crate) type EventSender = Box<dyn FnOnce(&mut World)>;
+
+/// An `EXTERNAL` function bound with [InkStories::bind_external_to_event].
+struct EventSink(Rc<dyn Fn(Vec<VariableValue>)>);
+
+impl ExternalFunction for EventSink {
+    fn call(&mut self, _name: &str, args: Vec<ValueType>) -> Option<ValueType> {
+        (self.0)(
+            args.iter()
+                .filter_map(VariableValue::from_value_type)
+                .collect(),
+        );
+        None
+    }
+}
+
+/// Sends the events made by `EXTERNAL` calls bound with
+/// [InkStories::bind_external_to_event].
+pub fn send_external_events(world: &mut World) {
+    let senders = world
+        .non_send_resource_mut::<InkStories>()
+        .external_events
+        .take();
+    for send in senders {
+        send(world);
+    }
+}
+
+/// A story's global variables as of the start of its current continue,
+/// shared with the external functions bound with
+/// [InkStories::bind_external_function_with_variables].
+pub(crate) struct SharedVariables {
+    names: Vec<String>,
+    values: Rc<RefCell<HashMap<String, VariableValue>>>,
+}
+
+impl SharedVariables {
+    /// Copies the story's current values. The story can't be read during an
+    /// external call, as it's busy continuing, so this happens beforehand.
+    pub(crate) fn refresh(&self, story: &Story) {
+        let mut values = self.values.borrow_mut();
+        values.clear();
+        values.extend(self.names.iter().filter_map(|name| {
+            let value = story.get_variable(name)?;
+            VariableValue::from_value_type(&value).map(|value| (name.clone(), value))
+        }));
+    }
+}
+
+/// A Rust function bound with
+/// [InkStories::bind_external_function_with_variables].
+struct RustFunctionWithVariables {
+    function: VariablesFunction,
+    values: Rc<RefCell<HashMap<String, VariableValue>>>,
+}
+
+/// An external function that also reads the story's global variables by name.
+pub type ExternalWithVariables =
+    Box<dyn Fn(Vec<VariableValue>, &HashMap<String, VariableValue>) -> VariableValue>;
+
+/// An [ExternalWithVariables] shared between a story and its reloads.
+type VariablesFunction =
+    Rc<dyn Fn(Vec<VariableValue>, &HashMap<String, VariableValue>) -> VariableValue>;
+
+impl ExternalFunction for RustFunctionWithVariables {
+    fn call(&mut self, name: &str, args: Vec<ValueType>) -> Option<ValueType> {
+        let args = args
+            .iter()
+            .filter_map(VariableValue::from_value_type)
+            .collect();
+        let value = (self.function)(args, &self.values.borrow());
+        let result = value.to_value_type();
+        if result.is_none() {
+            warn!("External function {name:?} returned {value:?}, which ink can't take back");
+        }
+        result
+    }
+}
+
+/// How an `EXTERNAL` function was bound for one story, kept to bind it again
+/// in the story parsed to replace it, e.g., on hot reload.
+#[derive(Clone)]
+pub(crate) enum StoryExternal {
+    Function(GlobalExternal),
+    WithVariables(VariablesFunction),
+    Event(Rc<dyn Fn(Vec<VariableValue>)>),
+    Async,
+}
+
+impl InkEntry {
+    /// Binds `name` for this story alone and keeps how, for
+    /// [InkEntry::rebind_externals].
+    pub(crate) fn bind_own(
+        &mut self,
+        name: &str,
+        external: StoryExternal,
+    ) -> Result<(), StoryError> {
+        match &external {
+            StoryExternal::Function(function) => self.bind(name, RustFunction(function.clone())),
+            StoryExternal::WithVariables(function) => {
+                let view = self.variables_view.get_or_insert_with(|| SharedVariables {
+                    names: self.variable_names.clone(),
+                    values: default(),
+                });
+                let values = view.values.clone();
+                let function = function.clone();
+                self.bind(name, RustFunctionWithVariables { function, values })
+            }
+            StoryExternal::Event(sink) => self.bind(name, EventSink(sink.clone())),
+            StoryExternal::Async => {
+                let calls = self.async_calls.get_or_insert_with(default).clone();
+                self.bind(name, AsyncExternal(calls))
+            }
+        }?;
+        self.own_externals.insert(name.to_string(), external);
+        Ok(())
+    }
+
+    /// Binds the functions `old` bound for itself in this story, which
+    /// replaces it.
+    pub(crate) fn rebind_externals(&mut self, id: Entity, old: &InkEntry) {
+        for (name, external) in &old.own_externals {
+            if let Err(err) = self.bind_own(name, external.clone()) {
+                warn!("Could not bind ink external {name:?} again in {id}: {err}");
+            }
+        }
+    }
+
+    /// Binds every `EXTERNAL` function that isn't bound yet and has no ink
+    /// fallback to raise [InkEvent::ExternalCall].
+    pub(crate) fn forward_calls(&mut self, id: Entity) {
+        for name in compiled::unbacked_external_names(&self.json) {
+            let forwarder = Forwarder {
+                entity: id,
+                calls: self.story_events.clone(),
+            };
+            // Fails only when the function is already bound, which we respect.
+            let _ = self.bind(&name, forwarder);
+        }
+        self.forwards_calls = true;
+    }
+
+    /// Binds `name` in the story and remembers it for
+    /// [InkStories::has_external].
+    pub(crate) fn bind(
+        &mut self,
+        name: &str,
+        function: impl ExternalFunction + 'static,
+    ) -> Result<(), StoryError> {
+        // A story's own binding replaces a global one.
+        if self.global_externals.remove(name) {
+            self.story.unbind_external_function(name)?;
+        }
+        self.story
+            .bind_external_function(name, Rc::new(RefCell::new(function)), false)?;
+        self.bound_externals.insert(name.to_string());
+        Ok(())
+    }
+
+    /// Binds each of `globals` that the story hasn't bound itself, replacing
+    /// any global bound before under the same name.
+    pub(crate) fn bind_globals(&mut self, globals: &HashMap<String, GlobalExternal>) {
+        for (name, function) in globals {
+            if self.bound_externals.contains(name) && !self.global_externals.contains(name) {
+                continue;
+            }
+            let function = function.clone();
+            // Fails only when the function is bound on the story directly,
+            // which we respect.
+            if self.bind(name, RustFunction(function)).is_ok() {
+                self.global_externals.insert(name.clone());
+            }
+        }
+    }
+}
+
+impl InkStories {
+    /// Binds the `EXTERNAL` function `name` to `function`, whose return value
+    /// goes back to the story, e.g., for `EXTERNAL random_name()`. Lists and
+    /// divert targets can't be returned.
+    ///
+    /// Bind before the story first continues, or this errors with
+    /// [InkError::AlreadyStarted]; ink checks its externals are bound then.
+    /// The binding carries over to the story's reloads.
+    pub fn bind_external_function(
+        &mut self,
+        id: Entity,
+        name: &str,
+        function: Box<dyn Fn(Vec<VariableValue>) -> VariableValue>,
+    ) -> Result<(), InkError> {
+        let entry = self.entry_mut(id)?;
+        if entry.current_line.is_some() {
+            return Err(InkError::AlreadyStarted);
+        }
+        entry.bind_own(name, StoryExternal::Function(function.into()))?;
+        Ok(())
+    }
+
+    /// Binds the `EXTERNAL` function `name` to `function` in every story,
+    /// those loaded now and those parsed later, e.g., for a `debug_log` all
+    /// of them share. A story's own binding with the same name, made with
+    /// [InkStories::bind_external_function] or the like, replaces it.
+    /// Stories that have already bound `name` keep their own.
+    pub fn bind_global_external(
+        &mut self,
+        name: &str,
+        function: impl Fn(Vec<VariableValue>) -> VariableValue + 'static,
+    ) {
+        let function: GlobalExternal = Rc::new(function);
+        let globals = HashMap::from_iter([(name.to_string(), function.clone())]);
+        for entry in self.stories.values_mut() {
+            entry.bind_globals(&globals);
+        }
+        self.global_externals.insert(name.to_string(), function);
+    }
+
+    /// Like [InkStories::bind_external_function], but `function` also gets the
+    /// story's global variables by name, e.g., for an external that prices
+    /// an item by the player's `reputation`.
+    ///
+    /// The story is mid-continue during the call, so the variables are as of
+    /// the start of that continue: changes the ink made since, e.g., a `~ x =
+    /// 1` on the same line, aren't seen. Pass such values as arguments.
+    pub fn bind_external_function_with_variables(
+        &mut self,
+        id: Entity,
+        name: &str,
+        function: ExternalWithVariables,
+    ) -> Result<(), InkError> {
+        let entry = self.entry_mut(id)?;
+        if entry.current_line.is_some() {
+            return Err(InkError::AlreadyStarted);
+        }
+        entry.bind_own(name, StoryExternal::WithVariables(function.into()))?;
+        Ok(())
+    }
+
+    /// Binds the `EXTERNAL` function `name` to send the Bevy event `mapper`
+    /// makes of its arguments, e.g., `EXTERNAL emit_event(name, value)` to a
+    /// typed event for an ECS system. The event is sent once [InkSystems] next
+    /// run and must have been added with `App::add_event`. The call returns
+    /// nothing to ink, so this suits void externals.
+    ///
+    /// Bind before the story first continues, as with
+    /// [InkStories::bind_external_function].
+    pub fn bind_external_to_event<E: Event>(
+        &mut self,
+        id: Entity,
+        name: &str,
+        mapper: impl Fn(Vec<VariableValue>) -> E + 'static,
+    ) -> Result<(), InkError> {
+        let events = self.external_events.clone();
+        let entry = self.entry_mut(id)?;
+        if entry.current_line.is_some() {
+            return Err(InkError::AlreadyStarted);
+        }
+        let sink = Rc::new(move |args| {
+            let event = mapper(args);
+            events.borrow_mut().push(Box::new(move |world: &mut World| {
+                world.send_event(event);
+            }));
+        });
+        entry.bind_own(name, StoryExternal::Event(sink))?;
+        Ok(())
+    }
+
+    /// Returns the names of the `EXTERNAL` functions the story calls, sorted,
+    /// e.g., to check at startup that each one has been bound. Includes those
+    /// with an ink fallback.
+    pub fn external_function_names(&self, id: Entity) -> Result<Vec<String>, InkError> {
+        Ok(compiled::external_names(&self.entry(id)?.json))
+    }
+
+    /// Binds every `EXTERNAL` function the story calls, that isn't bound yet
+    /// and has no ink fallback, to raise [InkEvent::ExternalCall] instead,
+    /// e.g., to play a sound from a normal Bevy system.
+    ///
+    /// These calls return nothing to ink, so this suits void externals. Bind
+    /// functions that return values before calling this; they are left alone.
+    pub fn forward_external_calls(&mut self, id: Entity) -> Result<(), InkError> {
+        self.entry_mut(id)?.forward_calls(id);
+        Ok(())
+    }
+
+    /// Whether the `EXTERNAL` function `name` has been bound through these
+    /// methods, e.g., to check every name in
+    /// [InkStories::external_function_names] is covered. Functions bound on
+    /// the [Story] directly aren't seen; bladeink doesn't tell.
+    pub fn has_external(&self, id: Entity, name: &str) -> Result<bool, InkError> {
+        Ok(self.entry(id)?.bound_externals.contains(name))
+    }
+
+    /// Returns the names of the `EXTERNAL` functions bound as
+    /// [InkStories::has_external] sees them, sorted.
+    pub fn bound_external_names(&self, id: Entity) -> Result<Vec<String>, InkError> {
+        let mut names: Vec<String> = self.entry(id)?.bound_externals.iter().cloned().collect();
+        names.sort();
+        Ok(names)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROLL: &str = include_str!("../assets/roll.ink.json");
+
+    #[test]
+    fn externals_are_bound_again_on_reparse() {
+        let mut ink_stories = InkStories::default();
+        let id = Entity::from_raw(0);
+        ink_stories.parse_from_str(id, ROLL).unwrap();
+        ink_stories
+            .bind_external_function(id, "roll", Box::new(|_| VariableValue::Int(4)))
+            .unwrap();
+        // Reparsing, as a hot reload does, keeps the binding.
+        ink_stories.parse_from_str(id, ROLL).unwrap();
+        assert!(ink_stories.has_external(id, "roll").unwrap());
+        assert_eq!(ink_stories.cont(id).unwrap(), "You rolled 4.\n");
+    }
+
+    #[test]
+    fn async_externals_are_bound_again_on_reparse() {
+        let mut ink_stories = InkStories::default();
+        let id = Entity::from_raw(0);
+        ink_stories.parse_from_str(id, ROLL).unwrap();
+        ink_stories.bind_async_external(id, "roll").unwrap();
+        ink_stories.parse_from_str(id, ROLL).unwrap();
+        assert!(matches!(
+            ink_stories.cont(id),
+            Err(InkError::ExternalPending(name)) if name == "roll"
+        ));
+        ink_stories
+            .resolve_external(id, VariableValue::Int(6))
+            .unwrap();
+        assert_eq!(ink_stories.cont(id).unwrap(), "You rolled 6.\n");
+    }
+}
@@ -0,0 +1,49 @@
+use super::*;
+
+/// Add to a story entity to keep an [InkHistory] of the lines it continues
+/// through, e.g., for a dialogue scrollback. `max_lines` bounds it by
+/// dropping the oldest lines; `None` keeps them all.
+#[derive(Debug, Component, Clone, Default)]
+#[require(InkHistory)]
+pub struct TrackHistory {
+    pub max_lines: Option<usize>,
+}
+
+/// Every line produced by [InkStories::cont] since the story started, oldest
+/// first. Cleared when it starts over with [InkStories::reset_state] or a hot
+/// reload that doesn't keep its state.
+#[derive(Debug, Component, Clone, Default)]
+pub struct InkHistory(pub Vec<String>);
+
+/// Adds each continued line to its story's [InkHistory].
+pub fn track_history(
+    mut events: EventReader<InkEvent>,
+    mut tracked: Query<(&TrackHistory, &mut InkHistory)>,
+) {
+    for ev in events.read() {
+        match ev {
+            InkEvent::OnContinue { entity, text, .. } => {
+                if let Ok((track, mut history)) = tracked.get_mut(*entity) {
+                    history.0.push(text.clone());
+                    if let Some(max_lines) = track.max_lines
+                        && history.0.len() > max_lines
+                    {
+                        let excess = history.0.len() - max_lines;
+                        history.0.drain(..excess);
+                    }
+                }
+            }
+            InkEvent::OnStoryReset { entity }
+            | InkEvent::OnStoryReload {
+                entity,
+                preserved: false,
+                ..
+            } => {
+                if let Ok((_, mut history)) = tracked.get_mut(*entity) {
+                    history.0.clear();
+                }
+            }
+            _ => {}
+        }
+    }
+}
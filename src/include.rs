@@ -0,0 +1,160 @@
+//! Compiles ink source whose `INCLUDE`s name other files.
+//!
+//! A source compiled from stdin has nowhere to resolve its includes from, so
+//! the source and everything it includes go into a temporary directory laid
+//! out like the assets, and the processor compiles the file there.
+use super::*;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Returns the files named by the `INCLUDE` lines of `source`.
+fn include_paths(source: &[u8]) -> Vec<PathBuf> {
+    String::from_utf8_lossy(source)
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("INCLUDE "))
+        .map(|path| PathBuf::from(path.trim()))
+        .collect()
+}
+
+/// Reads the files `source` includes, and the files they include in turn,
+/// through the asset server so changing them also reloads the source.
+///
+/// Like inklecate, includes resolve relative to the directory of the source,
+/// whichever file names them. The paths returned are relative to it too.
+pub(crate) async fn read_includes(
+    source: &[u8],
+    load_context: &mut LoadContext<'_>,
+) -> Result<Vec<(PathBuf, Vec<u8>)>, InkError> {
+    let dir = load_context
+        .path()
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    let mut includes: Vec<(PathBuf, Vec<u8>)> = Vec::new();
+    let mut queue = include_paths(source);
+    while let Some(path) = queue.pop() {
+        if includes.iter().any(|(seen, _)| *seen == path) {
+            continue;
+        }
+        if path
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir))
+        {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("INCLUDE {path:?} must be below the including file's directory"),
+            )
+            .into());
+        }
+        let bytes = load_context
+            .read_asset_bytes(dir.join(&path))
+            .await
+            .map_err(std::io::Error::other)?;
+        queue.extend(include_paths(&bytes));
+        includes.push((path, bytes));
+    }
+    Ok(includes)
+}
+
+impl InkProcessor {
+    /// Compiles ink source named `name` along with the files it includes, as
+    /// returned by [read_includes].
+    pub(crate) fn compile_with_includes(
+        &self,
+        name: &str,
+        source: &[u8],
+        includes: &[(PathBuf, Vec<u8>)],
+        flags: &[String],
+        timeout: Duration,
+    ) -> Result<String, InkError> {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "bevy_ink-{}-{}",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        let result = (|| {
+            std::fs::create_dir_all(&dir)?;
+            for (path, bytes) in includes {
+                let path = dir.join(path);
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::write(path, bytes)?;
+            }
+            let root = dir.join(name);
+            std::fs::write(&root, source)?;
+            self.compile_file(&root, flags, timeout)
+        })();
+        let _ = std::fs::remove_dir_all(&dir);
+        result
+    }
+
+    /// Compiles the ink source file at `path`, so its includes resolve
+    /// relative to it.
+    fn compile_file(
+        &self,
+        path: &Path,
+        flags: &[String],
+        timeout: Duration,
+    ) -> Result<String, InkError> {
+        let output = match self {
+            InkProcessor::Inklecate => run_with_timeout(
+                Command::new("inklecate")
+                    .args(flags)
+                    .arg("-o")
+                    .arg("/dev/stdout")
+                    .arg(path),
+                None,
+                timeout,
+            )?,
+            // A custom processor reads the source from stdin as usual, but in
+            // the file's directory, where its includes are.
+            InkProcessor::Custom { .. } => run_with_timeout(
+                self.command_with_args(flags)
+                    .current_dir(path.parent().unwrap_or(Path::new(".")))
+                    .stdin(Stdio::piped()),
+                Some(std::fs::read(path)?),
+                timeout,
+            )?,
+        };
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_source_with_includes_compiles_beside_them() {
+        let mut app = crate::tests::app();
+        // Stands in for inklecate: compiles to a line of the town that
+        // `places/town.ink`, included from `include/main.ink`, names.
+        let script = r#"cat > /dev/null
+town=$(sed -n 's/^VAR town = "\(.*\)"$/\1/p' places/town.ink)
+printf '{"inkVersion":21,"root":[["^%s","\\n","end",null],"done",null],"listDefs":{}}' "$town""#;
+        let processor = InkProcessor::Custom {
+            command: "sh".into(),
+            arguments: vec!["-c".into(), script.into()],
+        };
+        let handle = app
+            .world()
+            .resource::<AssetServer>()
+            .load_with_settings("include/main.ink", move |settings: &mut LoaderSettings| {
+                settings.processor = Some(processor.clone())
+            });
+        let id = app.world_mut().spawn(InkLoad(handle)).id();
+        let started = Instant::now();
+        while !app.world().entity(id).contains::<InkStory>() {
+            assert!(
+                started.elapsed() < Duration::from_secs(10),
+                "story never loaded"
+            );
+            app.update();
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        let mut ink_stories = app.world_mut().non_send_resource_mut::<InkStories>();
+        assert_eq!(ink_stories.cont(id).unwrap(), "Riverton\n");
+    }
+}
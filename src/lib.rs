@@ -1,25 +1,127 @@
-use bevy::asset::{AssetEvent, AssetLoader, LoadContext, io::Reader};
+#[cfg(feature = "inklecate")]
+use async_lock::Semaphore;
+use bevy::asset::{
+    AssetEvent, AssetLoader, AssetPath, LoadContext, LoadState, LoadedFolder, io::Reader,
+};
+use bevy::ecs::query::QueryData;
 use bevy::platform::collections::{HashMap, HashSet};
 use bevy::prelude::*;
-use bevy::reflect::TypeRegistry;
-use bladeink::{story::Story, story_error::StoryError};
+use bladeink::{
+    story::{
+        Story,
+        errors::{ErrorHandler, ErrorType},
+    },
+    story_error::StoryError,
+};
 use serde::{Deserialize, Serialize};
-use std::io::Write;
-use std::process::{Command, Stdio};
+use std::cell::RefCell;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use thiserror::Error;
 
+mod autoplay;
+mod awaiting;
+mod bundle;
+#[cfg(feature = "inklecate")]
+mod cache;
+mod channel;
+mod choices;
+mod compiled;
+mod externals;
+mod history;
+#[cfg(feature = "inklecate")]
+mod include;
+mod lists;
+mod param;
+mod park;
+mod policy;
+#[cfg(feature = "inklecate")]
+mod process;
 #[cfg(feature = "scripting")]
 pub mod scripting;
+mod state;
+mod step;
+mod tags;
+mod undo;
+mod variables;
+
+pub use autoplay::*;
+pub use awaiting::*;
+pub use bundle::*;
+pub use channel::*;
+pub use choices::*;
+pub use externals::*;
+pub use history::*;
+pub use param::*;
+pub use park::*;
+pub use policy::*;
+#[cfg(feature = "inklecate")]
+pub use process::*;
+pub use state::*;
+pub use step::*;
+pub use tags::*;
+pub use undo::*;
+pub use variables::*;
 
 pub struct InkPlugin;
 
+/// The plugin's systems, e.g., to run game logic `.after(InkSystems)` once
+/// stories have loaded, reloaded, and sent their events for the frame.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InkSystems;
+
 impl Plugin for InkPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<InkEvent>()
-            .init_non_send_resource::<InkStories>()
+            .add_event::<InkAdvanceRequest>()
+            .init_resource::<InkSettings>();
+        let settings = app.world().resource::<InkSettings>();
+        let loader = InkTextLoader::new(settings.max_parallel_compiles)
+            .with_extensions(&settings.extensions);
+        app.init_non_send_resource::<InkStories>()
             .init_asset::<InkText>()
-            .init_asset_loader::<InkTextLoader>()
-            .add_systems(Update, (load_on_add_then_poll, hot_reload_on_modify));
+            .register_asset_loader(loader)
+            .init_asset::<InkVarsAsset>()
+            .register_asset_loader(InkVarsLoader)
+            .register_type::<InkVariables>()
+            .add_systems(
+                Update,
+                (
+                    load_on_add_then_poll,
+                    hot_reload_on_modify,
+                    (
+                        process_ink_commands,
+                        select_choices,
+                        advance_on_request,
+                        auto_play,
+                        auto_select_single_choices,
+                        send_ink_events,
+                        (
+                            track_current_line,
+                            track_choices,
+                            track_history,
+                            sync_choice_cursors,
+                        ),
+                    )
+                        .chain(),
+                    sync_ink_variables,
+                    sync_choice_filters,
+                    sync_state_stacks,
+                    sync_step_events,
+                    send_external_events,
+                    store_auto_saves,
+                    prune_despawned_stories,
+                )
+                    .in_set(InkSystems),
+            );
+        app.add_systems(Last, save_on_exit);
+        #[cfg(feature = "inklecate")]
+        app.register_asset_processor(InkCompileProcessor::from(InkJsonSaver))
+            .set_default_asset_processor::<InkCompileProcessor>("ink");
         #[cfg(feature = "scripting")]
         app.add_plugins(scripting::plugin);
     }
@@ -31,121 +133,1707 @@ pub enum InkError {
     NotLoaded,
     #[error("no such story {0:?}")]
     NoSuchStory(Entity),
-    #[error("story error: {0:?}")]
+    #[error("{}", story_error_message(.0))]
     StoryError(#[from] StoryError),
     #[error("no processor defined to translate .ink to .ink.json")]
     NoProcessor,
     #[error("io error: {0}")]
     IoError(#[from] std::io::Error),
+    #[error("no current choice with text {0:?}")]
+    NoSuchChoice(String),
+    #[error("the story has already started")]
+    AlreadyStarted,
+    #[error("continued {0} lines without stopping; the story may loop forever")]
+    ContinueLimitExceeded(usize),
+    #[error("choice index {index} is out of range; there are {available} choices")]
+    ChoiceIndexOutOfRange { index: usize, available: usize },
+    #[error("variable {0:?} can't be set to that kind of value")]
+    CannotSetVariable(String),
+    #[error("no list item {0:?}")]
+    NoSuchListItem(String),
+    #[error("no variable {0:?}")]
+    NoSuchVariable(String),
+    #[error("variable {0:?} is not a list")]
+    NotAList(String),
+    #[error("the story has text left to continue before its choices")]
+    NotAtChoicePoint,
+    #[error("variable {name:?} is not {expected}")]
+    VariableTypeMismatch {
+        name: String,
+        expected: &'static str,
+    },
+    #[error("the story isn't in a knot or stitch")]
+    NotInKnot,
+    #[error("bad variable preset: {0}")]
+    BadVarsPreset(String),
+    #[error("no parked story {0:?}")]
+    NoParkedStory(String),
+    #[error("the story waits on the async external {0:?}")]
+    ExternalPending(String),
+    #[error("the story waits on no async external")]
+    NoPendingExternal,
+}
+
+/// Words bladeink's error for an author reading it in, e.g., an in-game error
+/// panel: its own text rather than its `Debug` form, without advice that only
+/// applies to bladeink's API, and with several issues as a list. Runtime errors
+/// say where they happened as a path in the story, like `(knot.0.3)`; compiled
+/// ink keeps no line numbers.
+fn story_error_message(err: &StoryError) -> String {
+    let message = err.to_string().replace(
+        " It is strongly suggested that you assign an error handler to story.onError.",
+        "",
+    );
+    let issues: Vec<&str> = message
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+    match issues.as_slice() {
+        [first, rest @ ..] if !rest.is_empty() => {
+            let mut list = first.to_string();
+            for issue in rest {
+                list.push_str("\n  - ");
+                list.push_str(issue.trim());
+            }
+            list
+        }
+        _ => message,
+    }
+}
+
+impl InkError {
+    /// Returns bladeink's own message for a story error, without the kind of
+    /// error it prefixes; otherwise the error as displayed.
+    pub fn message(&self) -> String {
+        match self {
+            InkError::StoryError(
+                StoryError::InvalidStoryState(message)
+                | StoryError::BadJson(message)
+                | StoryError::BadArgument(message),
+            ) => message.clone(),
+            err => err.to_string(),
+        }
+    }
+
+    /// Returns where in the story a runtime error happened as a path, e.g.,
+    /// `"knot.0.3"` from `RUNTIME ERROR: (knot.0.3): ...`.
+    pub fn story_path(&self) -> Option<String> {
+        let message = self.message();
+        let (_, rest) = message.split_once("RUNTIME ")?;
+        let (_, rest) = rest.split_once(": (")?;
+        rest.split_once("):").map(|(path, _)| path.to_string())
+    }
+
+    /// Returns the line and, if given, column an error's message names, e.g.,
+    /// from `line 3 column 14`, for an editor to mark. Compiled ink keeps no
+    /// source line numbers, so bladeink only names them for some errors
+    /// reading JSON; see [InkError::story_path] for runtime errors.
+    pub fn line_column(&self) -> Option<(usize, Option<usize>)> {
+        let message = self.message();
+        let number_after = |word: &str| {
+            let (_, rest) = message.split_once(word)?;
+            let digits: String = rest
+                .trim_start()
+                .chars()
+                .take_while(char::is_ascii_digit)
+                .collect();
+            digits.parse::<usize>().ok()
+        };
+        Some((number_after("line ")?, number_after("column ")))
+    }
+}
+
+#[derive(Debug, Resource, Clone)]
+pub struct InkSettings {
+    /// Whether stories are reparsed when their ink asset changes. Turn off,
+    /// e.g., in release builds so a changed asset can't wipe the player's
+    /// progress. See also [NoHotReload].
+    pub hot_reload: bool,
+    /// How long an ink asset must go unmodified before it is hot reloaded.
+    /// Coalesces the several saves some editors make in quick succession.
+    /// Measured on the virtual [Time], so pausing it holds reloads back.
+    pub reload_debounce: Duration,
+    /// How many `.ink` sources may be compiled at once, so loading dozens of
+    /// them doesn't fork a processor for each at the same time.
+    ///
+    /// Read when [InkPlugin] is added, so insert these settings before it.
+    pub max_parallel_compiles: usize,
+    /// More file extensions to load as compiled ink JSON besides `ink.json`,
+    /// e.g., `"inkjson"`. `"json"` would claim every `.json` asset, so only
+    /// add it if no other loader needs them.
+    ///
+    /// Read when [InkPlugin] is added, so insert these settings before it.
+    pub extensions: Vec<&'static str>,
+    /// How many stories [load_on_add_then_poll] parses in one frame. The rest
+    /// wait for later frames, so spawning dozens at once, e.g., loading a
+    /// save, doesn't stall a single frame.
+    pub parses_per_frame: usize,
+    /// How long [load_on_add_then_poll] waits for a story's ink asset before
+    /// giving up with [InkEvent::LoadFailed], e.g., on a path that never
+    /// resolves or a compile that hangs. `None` waits forever. Measured on
+    /// the virtual [Time], so a paused game doesn't time its loads out.
+    pub load_timeout: Option<Duration>,
+}
+
+impl Default for InkSettings {
+    fn default() -> Self {
+        Self {
+            hot_reload: true,
+            reload_debounce: Duration::from_millis(250),
+            max_parallel_compiles: 4,
+            extensions: Vec::new(),
+            parses_per_frame: 16,
+            load_timeout: Some(Duration::from_secs(30)),
+        }
+    }
 }
 
 #[derive(Debug, Event, Clone)]
 pub enum InkEvent {
-    OnStoryReload(Entity),
+    /// The story was hot reloaded. `preserved` is true if it carried on from
+    /// where it was thanks to [PreserveState]; otherwise it starts over.
+    /// `tags_changed` is true if its global tags differ from before, so a UI
+    /// showing, e.g., the title only needs to re-read [InkMeta] then.
+    OnStoryReload {
+        entity: Entity,
+        preserved: bool,
+        tags_changed: bool,
+    },
+    /// The story started over through [InkStories::reset_state].
+    OnStoryReset { entity: Entity },
+    /// The story continued through [InkStories::cont] and produced this line
+    /// and its tags.
+    OnContinue {
+        entity: Entity,
+        text: String,
+        tags: Vec<String>,
+    },
+    /// The story called an `EXTERNAL` function forwarded by
+    /// [InkStories::forward_external_calls].
+    ExternalCall {
+        entity: Entity,
+        name: String,
+        args: Vec<VariableValue>,
+    },
+    /// The story finished parsing and its entity got [InkStory], e.g., to
+    /// start its intro. Hot reloads raise [InkEvent::OnStoryReload] instead.
+    OnStoryLoaded { entity: Entity },
+    /// The story's ink asset or [InkVarsPreset] failed to load, e.g., its
+    /// path is wrong or it didn't compile, or it took longer than
+    /// [InkSettings::load_timeout]. The entity's [InkLoadState] says why.
+    LoadFailed { entity: Entity, path: String },
+    /// A variable observed with [InkStories::observe_variable] changed.
+    VariableChanged {
+        entity: Entity,
+        name: String,
+        value: VariableValue,
+    },
+    /// [InkStories::cont] left the story waiting on these choices, e.g., for
+    /// a UI to switch from showing text to showing a menu. Raised after the
+    /// line's [InkEvent::OnContinue].
+    OnChoicePoint {
+        entity: Entity,
+        choices: Vec<ChoiceInfo>,
+    },
+    /// The story's current choices differ from what they were after the last
+    /// [InkStories::cont] or [InkStories::choose_choice_index], including
+    /// when they empty out once one is chosen.
+    OnChoicesChanged {
+        entity: Entity,
+        choices: Vec<ChoiceInfo>,
+    },
+    /// The story's [InkRestore] state couldn't be loaded, so it starts over.
+    RestoreFailed { entity: Entity, message: String },
+    /// The story warned about something while continuing through
+    /// [InkStories::cont], e.g., a problem in its ink that isn't fatal, or a
+    /// line broke the [TagLint] when it was parsed.
+    Warning { entity: Entity, message: String },
+    /// The story's ink asset loaded but didn't parse, on first load or hot
+    /// reload, with where as far as [InkError::line_column] can tell.
+    ParseError {
+        entity: Entity,
+        message: String,
+        line: Option<usize>,
+        column: Option<usize>,
+    },
+    /// The story called an `EXTERNAL` function bound with
+    /// [InkStories::bind_async_external] and waits for
+    /// [InkStories::resolve_external] to give it a value.
+    ExternalPending {
+        entity: Entity,
+        name: String,
+        args: Vec<VariableValue>,
+    },
+    /// An [InkStepEvents] story continued a line, raised after the line's
+    /// other events.
+    OnStep(StepSnapshot),
+    /// A [SelectChoice] couldn't be chosen, e.g., its index is out of range.
+    ChoiceError {
+        entity: Entity,
+        index: usize,
+        message: String,
+    },
+}
+
+impl InkEvent {
+    fn parse_error(entity: Entity, err: InkError) -> Self {
+        let (line, column) = err
+            .line_column()
+            .map_or((None, None), |(line, column)| (Some(line), column));
+        InkEvent::ParseError {
+            entity,
+            message: err.to_string(),
+            line,
+            column,
+        }
+    }
+}
+
+pub struct InkStories {
+    stories: HashMap<Entity, InkEntry>,
+    /// Events raised by methods here, sent by [send_ink_events].
+    events: Vec<InkEvent>,
+    /// What [InkStories::cont] does when continuing fails, unless a story sets
+    /// its own with [InkStories::set_continue_error_policy].
+    pub continue_error_policy: ContinueErrorPolicy,
+    /// How [InkStories::cont] trims the lines it returns, unless a story sets
+    /// its own with [InkStories::set_text_trim_policy].
+    pub text_trim_policy: TextTrimPolicy,
+    /// The most lines [InkStories::cont_maximally],
+    /// [InkStories::run_to_end], and the choice previews such as
+    /// [InkStories::choices_at_path] continue before giving up with
+    /// [InkError::ContinueLimitExceeded], so a story that loops forever fails
+    /// loudly instead of freezing the frame.
+    pub max_continues: usize,
+    /// Whether choosing saves the story's state; see [AutoSaveOnChoice].
+    auto_save_on_choice: bool,
+    /// States saved on choosing, not yet stored by [store_auto_saves].
+    auto_saves: Vec<(Entity, String)>,
+    /// Compiled stories from [InkStories::reparse_from_str] waiting to be
+    /// swapped in.
+    source_reloads: Vec<(Entity, InkText)>,
+    /// The key of the `key: value` tag [InkStories::current_line_id] reads,
+    /// `"id"` by default as in `# id: greeting_01`.
+    pub line_id_tag: String,
+    /// Events made by `EXTERNAL` calls, sent by [send_external_events].
+    external_events: Rc<RefCell<Vec<EventSender>>>,
+    /// Stories parsed by [InkStories::park] with their compiled JSON, waiting
+    /// for an entity.
+    parked: HashMap<String, (Story, Rc<str>)>,
+    /// The `EXTERNAL` functions bound in every story; see
+    /// [InkStories::bind_global_external].
+    global_externals: HashMap<String, GlobalExternal>,
+    /// The JSON of each ink asset and whether it parsed, so the entities
+    /// playing the asset share one copy of it and a failure isn't parsed again;
+    /// see [load_on_add_then_poll].
+    templates: HashMap<AssetId<InkText>, InkTemplate>,
+    /// How many times compiled JSON was parsed into a story.
+    #[cfg(test)]
+    parses: usize,
+}
+
+/// The compiled JSON of an ink asset, which the stories of the entities
+/// playing that asset are parsed from.
+struct InkTemplate {
+    /// The compiled JSON, shared by every story parsed from the template.
+    json: Rc<str>,
+    /// Why the JSON didn't parse, if it didn't.
+    error: Option<String>,
+}
+
+impl Default for InkStories {
+    fn default() -> Self {
+        Self {
+            stories: default(),
+            events: default(),
+            continue_error_policy: default(),
+            text_trim_policy: default(),
+            max_continues: 5000,
+            auto_save_on_choice: false,
+            auto_saves: Vec::new(),
+            source_reloads: Vec::new(),
+            line_id_tag: "id".to_string(),
+            external_events: default(),
+            parked: default(),
+            global_externals: default(),
+            templates: default(),
+            #[cfg(test)]
+            parses: 0,
+        }
+    }
+}
+
+/// A line from [InkStories::cont_line].
+#[derive(Debug, Clone, PartialEq)]
+pub struct InkLine {
+    pub text: String,
+    pub tags: Vec<String>,
+    /// The line doesn't end in a newline and the story continues, so what
+    /// comes next belongs on the same line, e.g., because of glue (`<>`) or
+    /// an external function that stopped ink looking ahead.
+    pub glued: bool,
+}
+
+/// What to do when continuing a story fails, e.g., on a runtime divert error.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ContinueErrorPolicy {
+    /// Return the error.
+    #[default]
+    Propagate,
+    /// Log the error and continue to the next line if the story still can;
+    /// otherwise return an empty line.
+    Skip,
+    /// Log the error, reset the story to its start, and return an empty line.
+    Reset,
+}
+
+/// How [InkStories::cont] trims a line before returning it and sending it
+/// with [InkEvent::OnContinue].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TextTrimPolicy {
+    /// Keep the line as ink returns it, ending in a newline unless it's glued
+    /// to the next.
+    #[default]
+    Raw,
+    /// Drop the newline ending the line.
+    TrimTrailingNewline,
+    /// Drop whitespace from both ends of the line.
+    TrimWhitespace,
+}
+
+impl TextTrimPolicy {
+    /// Returns `line` trimmed by this policy.
+    pub fn apply(self, line: &str) -> &str {
+        match self {
+            TextTrimPolicy::Raw => line,
+            TextTrimPolicy::TrimTrailingNewline => line
+                .strip_suffix('\n')
+                .map(|line| line.strip_suffix('\r').unwrap_or(line))
+                .unwrap_or(line),
+            TextTrimPolicy::TrimWhitespace => line.trim(),
+        }
+    }
+}
+
+/// A parsed story plus what we track about it between calls.
+struct InkEntry {
+    story: Story,
+    /// The compiled JSON the story was parsed from, shared with the other
+    /// stories of the same asset.
+    json: Rc<str>,
+    /// Whether the JSON was loaded as is or compiled from source.
+    format: InkFormat,
+    /// The global variables the JSON declares, in declaration order, read
+    /// once here rather than each time they're needed.
+    variable_names: Vec<String>,
+    /// The line returned by the most recent [InkStories::cont].
+    current_line: Option<String>,
+    /// The lines continued since the last choice; see
+    /// [InkStories::current_buffered_text].
+    buffered_text: String,
+    /// Overrides [InkStories::continue_error_policy].
+    continue_error_policy: Option<ContinueErrorPolicy>,
+    /// Overrides [InkStories::text_trim_policy].
+    text_trim_policy: Option<TextTrimPolicy>,
+    /// Events raised from inside the story, e.g., [InkEvent::ExternalCall]s
+    /// made while continuing, not yet moved to [InkStories::events].
+    story_events: Rc<RefCell<Vec<InkEvent>>>,
+    /// The variables given observers by [InkStories::observe_variable].
+    observed: HashSet<String>,
+    /// The choices last sent with [InkEvent::OnChoicesChanged].
+    choices: Vec<ChoiceInfo>,
+    /// Hides choices; see [ChoiceFilter].
+    choice_filter: Option<ChoicePredicate>,
+    /// Runtime errors reported by the story's [IssueForwarder] during a
+    /// continue, not yet returned.
+    errors: Rc<RefCell<Vec<String>>>,
+    /// The runtime errors returned by continues since the story last started
+    /// over or loaded a state; see [InkStories::is_errored].
+    error_log: Vec<String>,
+    /// How many warnings and errors the story reported while parsing, before
+    /// its [IssueForwarder] was installed; see [InkMeta].
+    parse_issues: (usize, usize),
+    /// The variables shown to external functions bound with
+    /// [InkStories::bind_external_function_with_variables], if any are.
+    variables_view: Option<SharedVariables>,
+    /// The `EXTERNAL` functions bound through [InkStories]; see
+    /// [InkStories::has_external].
+    bound_externals: HashSet<String>,
+    /// Those of [InkEntry::bound_externals] bound from
+    /// [InkStories::bind_global_external], which a binding for this story
+    /// replaces.
+    global_externals: HashSet<String>,
+    /// Those of [InkEntry::bound_externals] bound for this story alone, to
+    /// bind again in the story parsed to replace it.
+    own_externals: HashMap<String, StoryExternal>,
+    /// Whether [InkStories::forward_external_calls] was called.
+    forwards_calls: bool,
+    /// Snapshots for [InkStories::undo]; see [InkStateStack].
+    undo_stack: Option<UndoStack>,
+    /// The calls to functions bound with [InkStories::bind_async_external],
+    /// if any are.
+    async_calls: Option<Rc<RefCell<AsyncCalls>>>,
+    /// Whether to raise [InkEvent::OnStep], and with variables; see
+    /// [InkStepEvents].
+    step_events: Option<bool>,
 }
 
-#[derive(Default)]
-pub struct InkStories(pub HashMap<Entity, Story>);
+impl InkEntry {
+    fn new(id: Entity, mut story: Story, json: Rc<str>) -> Self {
+        let story_events: Rc<RefCell<Vec<InkEvent>>> = default();
+        let errors: Rc<RefCell<Vec<String>>> = default();
+        let parse_issues = (
+            story.get_current_warnings().len(),
+            story.get_current_errors().len(),
+        );
+        story.set_error_handler(Rc::new(RefCell::new(IssueForwarder {
+            entity: id,
+            events: story_events.clone(),
+            errors: errors.clone(),
+        })));
+        Self {
+            story,
+            variable_names: compiled::global_variable_names(&json),
+            json,
+            format: InkFormat::Json,
+            current_line: None,
+            buffered_text: String::new(),
+            continue_error_policy: None,
+            text_trim_policy: None,
+            story_events,
+            observed: default(),
+            choices: Vec::new(),
+            choice_filter: None,
+            errors,
+            error_log: Vec::new(),
+            parse_issues,
+            variables_view: None,
+            bound_externals: default(),
+            global_externals: default(),
+            own_externals: default(),
+            forwards_calls: false,
+            undo_stack: None,
+            async_calls: None,
+            step_events: None,
+        }
+    }
+
+    /// Continues the story, failing with any errors it reported meanwhile.
+    fn cont(&mut self) -> Result<String, StoryError> {
+        if let Some(view) = &self.variables_view {
+            view.refresh(&self.story);
+        }
+        let line = self.story.cont()?;
+        let errors = self.errors.take();
+        if errors.is_empty() {
+            Ok(line)
+        } else {
+            self.error_log.extend(errors.iter().cloned());
+            Err(StoryError::InvalidStoryState(errors.join("\n")))
+        }
+    }
+}
+
+/// Queues the story's warnings as [InkEvent::Warning]s and keeps its errors
+/// for [InkEntry::cont] to return. Without a handler, bladeink fails a
+/// continue on warnings too.
+struct IssueForwarder {
+    entity: Entity,
+    events: Rc<RefCell<Vec<InkEvent>>>,
+    errors: Rc<RefCell<Vec<String>>>,
+}
+
+impl ErrorHandler for IssueForwarder {
+    fn error(&mut self, message: &str, error_type: ErrorType) {
+        match error_type {
+            ErrorType::Warning => self.events.borrow_mut().push(InkEvent::Warning {
+                entity: self.entity,
+                message: message.to_string(),
+            }),
+            ErrorType::Error => self.errors.borrow_mut().push(message.to_string()),
+        }
+    }
+}
 
 impl InkStories {
     /// Returns the prior story if there was one on success. Otherwise returns
     /// the error.
     pub fn try_parse(&mut self, id: Entity, ink: &InkText) -> Result<Option<Story>, StoryError> {
-        Story::new(&ink.0).map(|story| self.0.insert(id, story))
+        self.parse(id, &ink.0, ink.1)
+    }
+
+    /// Parses compiled ink JSON into a story for `id` without going through
+    /// the asset server. Returns the prior story if there was one.
+    ///
+    /// Together with [InkStories::take_events] this lets a story be driven
+    /// without an `App`, e.g., to unit test dialogue: start from
+    /// `InkStories::default()`, parse under any entity such as
+    /// `Entity::from_raw(0)`, then step it with [InkStories::cont] and
+    /// [InkStories::choose_choice_index].
+    pub fn parse_from_str(&mut self, id: Entity, json: &str) -> Result<Option<Story>, InkError> {
+        Ok(self.parse(id, json, InkFormat::Json)?)
+    }
+
+    /// Checks that compiled ink JSON parses into a story without keeping it,
+    /// e.g., to validate an editor's output as it's typed. Needs no
+    /// `InkStories`, so any system can call it.
+    pub fn validate(json: &str) -> Result<(), StoryError> {
+        Story::new(json).map(drop)
+    }
+
+    /// Removes and returns the events raised since the last call. In an `App`
+    /// these are sent as [InkEvent]s instead.
+    pub fn take_events(&mut self) -> Vec<InkEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    fn parse(
+        &mut self,
+        id: Entity,
+        json: &str,
+        format: InkFormat,
+    ) -> Result<Option<Story>, StoryError> {
+        let _span = info_span!("ink_parse", entity = %id).entered();
+        self.new_story(json)
+            .map(|story| self.insert(id, story, json.into(), format))
+    }
+
+    fn new_story(&mut self, json: &str) -> Result<Story, StoryError> {
+        #[cfg(test)]
+        {
+            self.parses += 1;
+        }
+        Story::new(json)
+    }
+
+    /// Parses the ink `asset` for `id` like [InkStories::try_parse], sharing
+    /// its JSON with every other entity that plays it. A failure is kept
+    /// instead of parsed again, until the asset's JSON changes.
+    fn parse_asset(
+        &mut self,
+        id: Entity,
+        asset: AssetId<InkText>,
+        ink: &InkText,
+    ) -> Result<Option<Story>, InkError> {
+        // A modified asset keeps its id, so check the template is its JSON.
+        if let Some(template) = self.templates.get(&asset)
+            && *template.json != *ink.0
+        {
+            self.templates.remove(&asset);
+        }
+        let template = self.templates.entry(asset).or_insert_with(|| InkTemplate {
+            json: ink.0.as_str().into(),
+            error: None,
+        });
+        if let Some(message) = &template.error {
+            return Err(StoryError::BadJson(message.clone()).into());
+        }
+        let json = template.json.clone();
+        let _span = info_span!("ink_parse", entity = %id).entered();
+        match self.new_story(&json) {
+            Ok(story) => Ok(self.insert(id, story, json, ink.1)),
+            Err(err) => {
+                let err = InkError::from(err);
+                if let Some(template) = self.templates.get_mut(&asset) {
+                    template.error = Some(err.message());
+                }
+                Err(err)
+            }
+        }
+    }
+
+    /// Makes `story` the story for `id`, keeping the settings and external
+    /// functions the prior one had, and returns the prior story.
+    fn insert(
+        &mut self,
+        id: Entity,
+        story: Story,
+        json: Rc<str>,
+        format: InkFormat,
+    ) -> Option<Story> {
+        let mut entry = InkEntry::new(id, story, json);
+        entry.format = format;
+        let mut forward = false;
+        if let Some(old) = self.stories.get(&id) {
+            entry.rebind_externals(id, old);
+            forward = old.forwards_calls;
+            entry.choice_filter = old.choice_filter.clone();
+            entry.continue_error_policy = old.continue_error_policy;
+            entry.text_trim_policy = old.text_trim_policy;
+            entry.step_events = old.step_events;
+            entry.undo_stack = old
+                .undo_stack
+                .as_ref()
+                .map(|undo| UndoStack::new(undo.depth));
+        }
+        entry.bind_globals(&self.global_externals);
+        if forward {
+            entry.forward_calls(id);
+        }
+        self.stories.insert(id, entry).map(|entry| entry.story)
+    }
+
+    /// Reloads the story from ink `source`, e.g., the buffer of an in-engine
+    /// editor, as though its file had changed: it keeps its state or
+    /// variables as [PreserveState] and [InheritVariables] say and raises
+    /// [InkEvent::OnStoryReload]. Compiling needs the `inklecate` feature.
+    ///
+    /// Errors if `source` doesn't compile or parse; otherwise the story is
+    /// swapped in the next time [InkSystems] run.
+    pub fn reparse_from_str(&mut self, id: Entity, source: &str) -> Result<(), InkError> {
+        self.entry(id)?;
+        let text = InkText::from_bytes(
+            source.as_bytes(),
+            InkFormat::Source,
+            Some(&InkProcessor::default()),
+        )?;
+        Self::validate(&text.0)?;
+        self.source_reloads.push((id, text));
+        Ok(())
     }
 
     pub fn get(&self, ink_story_ref: Entity) -> Result<&Story, InkError> {
-        self.0.get(&ink_story_ref).ok_or(InkError::NotLoaded)
+        self.entry(ink_story_ref).map(|entry| &entry.story)
     }
 
     pub fn get_mut(&mut self, ink_story_ref: Entity) -> Result<&mut Story, InkError> {
-        self.0.get_mut(&ink_story_ref).ok_or(InkError::NotLoaded)
+        self.entry_mut(ink_story_ref).map(|entry| &mut entry.story)
+    }
+
+    /// Like [InkStories::get], for when a missing story isn't an error.
+    pub fn try_get(&self, id: Entity) -> Option<&Story> {
+        self.stories.get(&id).map(|entry| &entry.story)
+    }
+
+    /// Like [InkStories::get_mut], for when a missing story isn't an error.
+    pub fn try_get_mut(&mut self, id: Entity) -> Option<&mut Story> {
+        self.stories.get_mut(&id).map(|entry| &mut entry.story)
+    }
+
+    /// Removes the story for `id`, returning it if there was one.
+    pub fn remove(&mut self, id: Entity) -> Option<Story> {
+        self.stories.remove(&id).map(|entry| entry.story)
+    }
+
+    /// Stops the story for `id` and frees it, returning it if there was one,
+    /// e.g., when a conversation's menu closes. Unlike [InkStories::remove]
+    /// this also drops its state waiting on [store_auto_saves] and any reload
+    /// from [InkStories::reparse_from_str]. To also
+    /// remove its components, see [InkCommandsExt::unload_ink].
+    pub fn unload(&mut self, id: Entity) -> Option<Story> {
+        self.auto_saves.retain(|(saved, _)| *saved != id);
+        self.source_reloads.retain(|(reloaded, _)| *reloaded != id);
+        self.remove(id)
+    }
+
+    /// Returns how many stories are loaded.
+    pub fn len(&self) -> usize {
+        self.stories.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stories.is_empty()
+    }
+
+    fn entry(&self, id: Entity) -> Result<&InkEntry, InkError> {
+        self.stories.get(&id).ok_or(InkError::NotLoaded)
+    }
+
+    fn entry_mut(&mut self, id: Entity) -> Result<&mut InkEntry, InkError> {
+        self.stories.get_mut(&id).ok_or(InkError::NotLoaded)
+    }
+
+    /// Returns the compiled JSON the story was parsed from, e.g., to export
+    /// the exact text behind the live story.
+    pub fn source_json(&self, id: Entity) -> Result<&str, InkError> {
+        Ok(&self.entry(id)?.json)
+    }
+
+    /// Returns an independent copy of the story in its current state, e.g., to
+    /// step ahead and preview where a choice leads, then throw away.
+    ///
+    /// This is a deep copy: the story is parsed again and its state carried
+    /// over through JSON, which can be expensive for large stories. The copy
+    /// has none of the story's external function bindings or observers.
+    pub fn fork(&self, id: Entity) -> Result<Story, InkError> {
+        let _span = info_span!("ink_fork", entity = %id).entered();
+        let entry = self.entry(id)?;
+        let mut story = Story::new(&entry.json)?;
+        story.load_state(&entry.story.save_state()?)?;
+        Ok(story)
+    }
+
+    /// Forks the story like [InkStories::fork] and keeps the copy as the story
+    /// for `fork_id`, so it can be driven through [InkStories] like any other.
+    /// The copy stays in memory until `fork_id`'s story is removed.
+    pub fn fork_to(&mut self, id: Entity, fork_id: Entity) -> Result<(), InkError> {
+        let story = self.fork(id)?;
+        let entry = self.entry(id)?;
+        let (json, format) = (entry.json.clone(), entry.format);
+        self.insert(fork_id, story, json, format);
+        Ok(())
+    }
+
+    /// Continue the story one line and return it.
+    ///
+    /// Raises [InkEvent::OnContinue], after any [InkEvent::ExternalCall]s and
+    /// [InkEvent::VariableChanged]s the line made. The line is trimmed by the
+    /// story's [TextTrimPolicy].
+    pub fn cont(&mut self, id: Entity) -> Result<String, InkError> {
+        self.cont_trimmed(id).map(|(line, _)| line)
+    }
+
+    /// Continues like [InkStories::cont] and also returns whether the
+    /// untrimmed line is glued to the next.
+    fn cont_trimmed(&mut self, id: Entity) -> Result<(String, bool), InkError> {
+        let _span = info_span!("ink_continue", entity = %id).entered();
+        let default_policy = self.continue_error_policy;
+        let default_trim = self.text_trim_policy;
+        let entry = self.entry_mut(id)?;
+        let replay = entry.begin_async()?;
+        entry.snapshot()?;
+        let result = entry.cont();
+        if let Some(PendingExternal { name, args }) = entry.end_async(replay)? {
+            entry.unsnapshot();
+            self.events.push(InkEvent::ExternalPending {
+                entity: id,
+                name: name.clone(),
+                args,
+            });
+            return Err(InkError::ExternalPending(name));
+        }
+        let line = match result {
+            Ok(line) => line,
+            Err(err) => match entry.continue_error_policy.unwrap_or(default_policy) {
+                ContinueErrorPolicy::Propagate => return Err(err.into()),
+                ContinueErrorPolicy::Skip => {
+                    warn!("Skipping ink line in {id} after error: {err}");
+                    if entry.story.can_continue() {
+                        entry.cont()?
+                    } else {
+                        String::new()
+                    }
+                }
+                ContinueErrorPolicy::Reset => {
+                    warn!("Resetting ink in {id} after error: {err}");
+                    entry.story.reset_state()?;
+                    String::new()
+                }
+            },
+        };
+        let glued = !line.is_empty() && !line.ends_with('\n') && entry.story.can_continue();
+        entry.buffered_text.push_str(&line);
+        let line = entry
+            .text_trim_policy
+            .unwrap_or(default_trim)
+            .apply(&line)
+            .to_string();
+        let tags = entry.story.get_current_tags()?;
+        entry.current_line = Some(line.clone());
+        self.raise_story_events(id);
+        self.events.push(InkEvent::OnContinue {
+            entity: id,
+            text: line.clone(),
+            tags: tags.clone(),
+        });
+        self.raise_choices_changed(id);
+        self.raise_choice_point(id);
+        self.raise_step(id, &line, &tags);
+        Ok((line, glued))
+    }
+
+    /// Jumps to `path`, e.g., `"knot.stitch"`, and continues one line there,
+    /// returning it like [InkStories::cont].
+    pub fn goto_and_continue(&mut self, id: Entity, path: &str) -> Result<String, InkError> {
+        self.get_mut(id)?.choose_path_string(path, true, None)?;
+        self.cont(id)
+    }
+
+    /// Moves the events raised from inside the story to [InkStories::events].
+    fn raise_story_events(&mut self, id: Entity) {
+        if let Some(entry) = self.stories.get(&id) {
+            let events = entry.story_events.take();
+            self.events.extend(events);
+        }
+    }
+
+    /// Loads `old`'s state into the story, e.g., to carry on from the same
+    /// place after a reload. If the state doesn't fit, the story is reset to
+    /// its start and the error returned.
+    pub fn preserve_state(&mut self, id: Entity, old: &Story) -> Result<(), InkError> {
+        let state = old.save_state()?;
+        let story = self.get_mut(id)?;
+        if let Err(err) = story.load_state(&state) {
+            story.reset_state()?;
+            return Err(err.into());
+        }
+        Ok(())
+    }
+
+    /// Continue the story until it stops for choices or ends and return the
+    /// lines, each raising [InkEvent::OnContinue] like [InkStories::cont].
+    ///
+    /// Gives up after [InkStories::max_continues] lines.
+    pub fn cont_maximally(&mut self, id: Entity) -> Result<Vec<String>, InkError> {
+        let mut lines = Vec::new();
+        while self.get(id)?.can_continue() {
+            if lines.len() >= self.max_continues {
+                return Err(InkError::ContinueLimitExceeded(lines.len()));
+            }
+            lines.push(self.cont(id)?);
+        }
+        Ok(lines)
+    }
+
+    /// Continue the story like [InkStories::cont_maximally], but stop after
+    /// the first line tagged `tag`, e.g., `"pause"` for `# pause`, to pace a
+    /// scene without choices. That line is the last returned. Stops early at
+    /// choices or the end.
+    ///
+    /// Gives up after [InkStories::max_continues] lines.
+    pub fn cont_until_tag(&mut self, id: Entity, tag: &str) -> Result<Vec<String>, InkError> {
+        let mut lines = Vec::new();
+        while self.get(id)?.can_continue() {
+            if lines.len() >= self.max_continues {
+                return Err(InkError::ContinueLimitExceeded(lines.len()));
+            }
+            lines.push(self.cont(id)?);
+            if self.current_tags(id)?.iter().any(|t| t.trim() == tag) {
+                break;
+            }
+        }
+        Ok(lines)
+    }
+
+    /// Continue the story like [InkStories::cont_maximally], but hand each
+    /// line and its tags to `f` as it's continued, e.g., to queue them for a
+    /// typewriter reveal. Returns how many lines there were.
+    ///
+    /// Gives up after [InkStories::max_continues] lines.
+    pub fn cont_maximally_with(
+        &mut self,
+        id: Entity,
+        mut f: impl FnMut(&str, &[String]),
+    ) -> Result<usize, InkError> {
+        let mut count = 0;
+        while self.get(id)?.can_continue() {
+            if count >= self.max_continues {
+                return Err(InkError::ContinueLimitExceeded(count));
+            }
+            let line = self.cont(id)?;
+            f(&line, &self.current_tags(id)?);
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Continues every story that can continue by one line, like
+    /// [InkStories::cont] on each, e.g., to advance many background
+    /// conversations in one world access. Stories at choices or their end, or
+    /// waiting on an async external, are left out of the result; see
+    /// [InkStories::can_continue].
+    ///
+    /// Nothing calls this for you, so stories stepped by hand are untouched
+    /// unless you do.
+    pub fn step_all_continuable(&mut self) -> HashMap<Entity, Result<String, InkError>> {
+        let ids: Vec<Entity> = self
+            .stories
+            .iter()
+            .filter(|(_, entry)| entry.story.can_continue() && !entry.awaits_external())
+            .map(|(id, _)| *id)
+            .collect();
+        ids.into_iter().map(|id| (id, self.cont(id))).collect()
+    }
+
+    /// Continue the story up to `n` lines, e.g., one sentence per keypress,
+    /// and return them. Stops early without error at choices or the end, or
+    /// while the story waits on an async external.
+    pub fn cont_n(&mut self, id: Entity, n: usize) -> Result<Vec<String>, InkError> {
+        let mut lines = Vec::new();
+        while lines.len() < n && self.can_continue(id)? {
+            lines.push(self.cont(id)?);
+        }
+        Ok(lines)
+    }
+
+    /// Continue the story one line like [InkStories::cont], returning the line
+    /// with its tags and whether it's glued to the next.
+    pub fn cont_line(&mut self, id: Entity) -> Result<InkLine, InkError> {
+        let (text, glued) = self.cont_trimmed(id)?;
+        let tags = self.current_tags(id)?;
+        Ok(InkLine { text, tags, glued })
+    }
+
+    /// Returns where the story is as a dotted knot and stitch path, e.g.,
+    /// `"chapter1.intro"`, for a debug HUD or a save point to go back to
+    /// with `choose_path_string`. Returns `None` before the first continue,
+    /// once the story has ended, and in top-level content outside any knot.
+    pub fn current_path(&self, id: Entity) -> Result<Option<String>, InkError> {
+        let entry = self.entry(id)?;
+        if entry.current_line.is_none() {
+            return Ok(None);
+        }
+        Ok(entry.story.get_current_path().and_then(|path| {
+            // Drop content indices and the containers ink names itself, such
+            // as gathers `g-0` and choices `c-0`, to leave the author's names.
+            let named: Vec<&str> = path
+                .split('.')
+                .filter(|component| {
+                    let generated = component.split_once('-').is_some_and(|(kind, n)| {
+                        matches!(kind, "c" | "g" | "s") && n.parse::<u32>().is_ok()
+                    });
+                    !component.is_empty()
+                        && component.parse::<u32>().is_err()
+                        && !generated
+                        && !component.starts_with('$')
+                })
+                .collect();
+            (!named.is_empty()).then(|| named.join("."))
+        }))
+    }
+
+    /// Returns how many times ink has counted the story entering the knot or
+    /// stitch at `path`, e.g., `"knot.stitch"`, as ink's `READ_COUNT`.
+    ///
+    /// Ink only counts knots whose counts the story reads somewhere, e.g., in
+    /// a condition on `knot`, unless compiled with
+    /// [LoaderSettings::count_all_visits]. Others stay at `0`.
+    pub fn visit_count(&self, id: Entity, path: &str) -> Result<u32, InkError> {
+        Ok(self.get(id)?.get_visit_count_at_path_string(path)?.max(0) as u32)
+    }
+
+    /// Returns whether the story is visiting the knot or stitch of
+    /// [InkStories::current_path] for the first time, for the "first time
+    /// here vs returning" idiom.
+    ///
+    /// Ink counts a visit on entering, so while in a knot for the first time
+    /// its [InkStories::visit_count] is already `1`; this is true while it's
+    /// at most `1`, including for knots whose visits ink doesn't count.
+    /// Errors with [InkError::NotInKnot] where `current_path` is `None`.
+    pub fn is_first_visit(&self, id: Entity) -> Result<bool, InkError> {
+        let path = self.current_path(id)?.ok_or(InkError::NotInKnot)?;
+        Ok(self.visit_count(id, &path)? <= 1)
+    }
+
+    /// Returns the names of the story's top-level knots, sorted, e.g., for a
+    /// debug menu that jumps to any of them with `choose_path_string`. Ink
+    /// functions are knots too, so they're included.
+    pub fn knot_names(&self, id: Entity) -> Result<Vec<String>, InkError> {
+        Ok(compiled::knot_names(&self.entry(id)?.json))
+    }
+
+    /// Continue the story through any glued fragments and return them joined
+    /// as one line, e.g., so a UI never breaks a line ink glued together.
+    pub fn cont_glued(&mut self, id: Entity) -> Result<String, InkError> {
+        let mut line = self.cont_line(id)?;
+        let mut text = std::mem::take(&mut line.text);
+        while line.glued {
+            line = self.cont_line(id)?;
+            text.push_str(&line.text);
+        }
+        Ok(text)
+    }
+
+    /// Sets what [InkStories::cont] does when continuing this story fails.
+    pub fn set_continue_error_policy(
+        &mut self,
+        id: Entity,
+        policy: ContinueErrorPolicy,
+    ) -> Result<(), InkError> {
+        self.entry_mut(id)?.continue_error_policy = Some(policy);
+        Ok(())
+    }
+
+    /// Sets how [InkStories::cont] trims this story's lines.
+    pub fn set_text_trim_policy(
+        &mut self,
+        id: Entity,
+        policy: TextTrimPolicy,
+    ) -> Result<(), InkError> {
+        self.entry_mut(id)?.text_trim_policy = Some(policy);
+        Ok(())
+    }
+
+    /// Whether continuing the story has hit a runtime error since it last
+    /// started over or loaded a state, e.g., to offer the player a way to
+    /// recover. bladeink ends the story on an error, whatever its
+    /// [ContinueErrorPolicy] does next.
+    pub fn is_errored(&self, id: Entity) -> Result<bool, InkError> {
+        let entry = self.entry(id)?;
+        Ok(!entry.error_log.is_empty() || entry.story.has_error())
+    }
+
+    /// Returns the runtime errors behind [InkStories::is_errored], oldest
+    /// first.
+    pub fn runtime_errors(&self, id: Entity) -> Result<Vec<String>, InkError> {
+        Ok(self.entry(id)?.error_log.clone())
+    }
+
+    /// Forgets the story's runtime errors so [InkStories::is_errored] is
+    /// false again, e.g., once the player has been told.
+    pub fn clear_runtime_errors(&mut self, id: Entity) -> Result<(), InkError> {
+        self.entry_mut(id)?.error_log.clear();
+        Ok(())
+    }
+
+    /// Returns every line continued since the story last took a choice, or
+    /// since it started, as ink returned them, e.g., for a renderer that lays
+    /// out the whole block before the choices at once.
+    ///
+    /// [InkStories::current_text] is only the last of these lines, and
+    /// [InkHistory] keeps the lines from before earlier choices too.
+    pub fn current_buffered_text(&self, id: Entity) -> Result<String, InkError> {
+        Ok(self.entry(id)?.buffered_text.clone())
+    }
+
+    /// Returns the line from the most recent [InkStories::cont] again without
+    /// advancing the story, or an empty string if it hasn't continued yet.
+    pub fn current_text(&self, id: Entity) -> Result<String, InkError> {
+        Ok(self.entry(id)?.current_line.clone().unwrap_or_default())
+    }
+
+    /// Continue the story and return the line that was current beforehand
+    /// together with the new one, e.g., to crossfade between them.
+    ///
+    /// The previous line is `None` on the first continue.
+    pub fn cont_with_previous(&mut self, id: Entity) -> Result<(Option<String>, String), InkError> {
+        let previous = self.entry(id)?.current_line.clone();
+        let line = self.cont(id)?;
+        Ok((previous, line))
     }
 }
 
+/// The ink asset a story entity plays. Changing it, e.g., with
+/// [InkCommandsExt::swap_ink], loads and parses the new asset in place of the
+/// old story, which plays on until then. Like a hot reload, [PreserveState]
+/// or [InheritVariables] carry the old story's state or variables over.
+/// Setting it to the asset already loaded does nothing.
 #[derive(Debug, Component, Clone)]
 pub struct InkLoad(pub Handle<InkText>);
 
+/// Spawns story entities from Rust like `ink_load` does from Lua.
+pub trait InkCommandsExt {
+    /// Loads the ink at `path` and spawns an entity with its [InkLoad],
+    /// e.g., for an NPC with dialogue. The entity gets [InkStory] once the
+    /// story is ready.
+    fn spawn_ink<'a>(
+        &mut self,
+        asset_server: &AssetServer,
+        path: impl Into<AssetPath<'a>>,
+    ) -> Entity;
+
+    /// Spawns a story entity for every ink asset in a loaded folder, e.g.,
+    /// from `asset_server.load_folder("dialogue")`, ignoring other assets.
+    /// Returns the entities by file name, e.g., `"intro.ink.json"`.
+    fn spawn_ink_folder(&mut self, folder: &LoadedFolder) -> HashMap<String, Entity>;
+
+    /// Points the story entity `id` at another ink asset, e.g., a localized
+    /// variant, by replacing its [InkLoad].
+    fn swap_ink(&mut self, id: Entity, ink: Handle<InkText>);
+
+    /// Moves the story parked under `key` onto `id`, which gets [InkStory]
+    /// without waiting on an asset; see [InkStories::park].
+    fn unpark_ink(&mut self, id: Entity, key: impl Into<String>);
+
+    /// Imports `bundle` as the story of `id`, which gets [InkStory] without
+    /// waiting on an asset; see [InkStories::import_bundle].
+    fn import_ink_bundle(&mut self, id: Entity, bundle: InkBundle);
+
+    /// Unloads the story of `id` and removes its [InkStory], [InkLoad], and
+    /// the components describing them, keeping the entity.
+    fn unload_ink(&mut self, id: Entity);
+}
+
+impl InkCommandsExt for Commands<'_, '_> {
+    fn spawn_ink<'a>(
+        &mut self,
+        asset_server: &AssetServer,
+        path: impl Into<AssetPath<'a>>,
+    ) -> Entity {
+        self.spawn(InkLoad(asset_server.load(path))).id()
+    }
+
+    fn spawn_ink_folder(&mut self, folder: &LoadedFolder) -> HashMap<String, Entity> {
+        folder
+            .handles
+            .iter()
+            .filter_map(|handle| {
+                let name = handle
+                    .path()?
+                    .path()
+                    .file_name()?
+                    .to_string_lossy()
+                    .into_owned();
+                let handle = handle.clone().try_typed::<InkText>().ok()?;
+                Some((name, self.spawn(InkLoad(handle)).id()))
+            })
+            .collect()
+    }
+
+    fn swap_ink(&mut self, id: Entity, ink: Handle<InkText>) {
+        self.entity(id).insert(InkLoad(ink));
+    }
+
+    fn unpark_ink(&mut self, id: Entity, key: impl Into<String>) {
+        let key = key.into();
+        self.queue(move |world: &mut World| {
+            if let Err(err) = unpark_ink(world, id, &key) {
+                error!("Could not unpark ink {key:?} onto {id}: {err}");
+            }
+        });
+    }
+
+    fn import_ink_bundle(&mut self, id: Entity, bundle: InkBundle) {
+        self.queue(move |world: &mut World| {
+            if let Err(err) = import_ink_bundle(world, id, &bundle) {
+                error!("Could not import ink bundle onto {id}: {err}");
+            }
+        });
+    }
+
+    fn unload_ink(&mut self, id: Entity) {
+        self.queue(move |world: &mut World| {
+            unload_ink(world, id);
+        });
+    }
+}
+
+/// Unloads the story of `id` as [InkCommandsExt::unload_ink] does. Returns
+/// whether it had one.
+pub fn unload_ink(world: &mut World, id: Entity) -> bool {
+    let unloaded = world
+        .non_send_resource_mut::<InkStories>()
+        .unload(id)
+        .is_some();
+    if let Ok(mut entity) = world.get_entity_mut(id) {
+        entity.remove::<(InkStory, InkLoad, InkLoadState, InkMeta)>();
+    }
+    unloaded
+}
+
+/// Marks a story entity whose story is parsed and set up, ready to use through
+/// [InkStories].
+///
+/// [load_on_add_then_poll] applies the entity's [InkInitVars], [InkSeed],
+/// the values of a write-back [InkVariables] it was spawned with, and
+/// [InkRestore] in the same run that parses its story and queues this, so no
+/// system ever sees the story with its variables not yet set.
 #[derive(Debug, Component, Clone)]
 pub struct InkStory;
 
+/// Where a story entity is in loading its ink, e.g., to show a spinner or
+/// an error badge. Maintained by [load_on_add_then_poll].
+#[derive(Debug, Component, Clone, PartialEq)]
+pub enum InkLoadState {
+    Loading,
+    /// The story is parsed; the entity also has [InkStory].
+    Ready,
+    /// The story failed to load or parse with this message.
+    Failed(String),
+}
+
+/// A story's compiled JSON and the format it was loaded from: source is
+/// compiled on load, so [InkFormat::Source] means the JSON came from an
+/// [InkProcessor] rather than from a `.ink.json` file.
 #[derive(Debug, Asset, TypePath)]
-pub struct InkText(pub String);
+pub struct InkText(pub String, pub InkFormat);
+
+/// What kind of ink some bytes hold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum InkFormat {
+    /// Compiled `.ink.json`.
+    Json,
+    /// `.ink` source that must be compiled by an [InkProcessor].
+    Source,
+}
+
+impl InkText {
+    /// Makes an `InkText` from bytes that don't come from the asset server,
+    /// e.g., from `include_bytes!`, compiling them with `processor` if they
+    /// are source. Source needs the `inklecate` feature.
+    ///
+    /// Add the result to `Assets<InkText>` and put the handle in an [InkLoad]
+    /// to play it like any other story.
+    pub fn from_bytes(
+        bytes: &[u8],
+        format: InkFormat,
+        processor: Option<&InkProcessor>,
+    ) -> Result<Self, InkError> {
+        match format {
+            InkFormat::Json => Ok(InkText(
+                String::from_utf8_lossy(bytes).into(),
+                InkFormat::Json,
+            )),
+            #[cfg(feature = "inklecate")]
+            InkFormat::Source => processor
+                .ok_or(InkError::NoProcessor)?
+                .compile(bytes)
+                .map(|json| InkText(json, InkFormat::Source)),
+            #[cfg(not(feature = "inklecate"))]
+            InkFormat::Source => {
+                let _ = processor;
+                Err(std::io::Error::new(
+                    std::io::ErrorKind::Unsupported,
+                    "loading .ink source requires bevy_ink's \"inklecate\" feature; \
+                     enable it or load compiled .ink.json instead",
+                )
+                .into())
+            }
+        }
+    }
+}
+
+/// Add to a story entity to keep its variables' values when it is hot
+/// reloaded. Otherwise a reload starts over with the story's defaults.
+#[derive(Debug, Component, Clone)]
+pub struct InheritVariables;
+
+/// Add to a story entity to keep it as it is when its ink asset changes.
+#[derive(Debug, Component, Clone)]
+pub struct NoHotReload;
+
+/// Add to a story entity to hold off hot reloading it, e.g., while the
+/// player is mid-conversation. A reload that comes in meanwhile is applied,
+/// with the asset as it is then, once this is removed.
+#[derive(Debug, Component, Clone)]
+pub struct DeferReload;
+
+/// Add to a story entity to carry on from the same place when it is hot
+/// reloaded, e.g., to live edit mid-conversation. If the old state doesn't
+/// fit the new story, it starts over with a warning.
+///
+/// This keeps variables too, so [InheritVariables] is only a fallback.
+#[derive(Debug, Component, Clone)]
+pub struct PreserveState;
+
+/// Add to a story entity to have [InkCurrentLine] and [InkCurrentTags] kept
+/// up to date as it continues.
+#[derive(Debug, Component, Clone)]
+pub struct TrackCurrentLine;
+
+/// The line most recently produced by [InkStories::cont].
+#[derive(Debug, Component, Clone)]
+pub struct InkCurrentLine(pub String);
+
+/// The tags of the line in [InkCurrentLine], replaced with each line, so a
+/// line without tags leaves it empty rather than keeping the last line's.
+#[derive(Debug, Component, Clone)]
+pub struct InkCurrentTags(pub Vec<String>);
+
+pub fn send_ink_events(mut ink_stories: NonSendMut<InkStories>, mut writer: EventWriter<InkEvent>) {
+    if !ink_stories.events.is_empty() {
+        writer.write_batch(ink_stories.take_events());
+    }
+}
+
+/// Drops the stories of entities that were despawned or lost [InkStory], so
+/// spawning and despawning many story entities doesn't leak them.
+pub fn prune_despawned_stories(
+    mut removed: RemovedComponents<InkStory>,
+    mut ink_stories: NonSendMut<InkStories>,
+) {
+    for id in removed.read() {
+        ink_stories.remove(id);
+    }
+}
+
+fn track_current_line(
+    mut events: EventReader<InkEvent>,
+    tracked: Query<(), With<TrackCurrentLine>>,
+    mut commands: Commands,
+) {
+    for ev in events.read() {
+        if let InkEvent::OnContinue { entity, text, tags } = ev
+            && tracked.contains(*entity)
+        {
+            commands
+                .entity(*entity)
+                .insert((InkCurrentLine(text.clone()), InkCurrentTags(tags.clone())));
+        }
+    }
+}
+
+/// The components that set up a story entity's story whenever it's parsed.
+#[derive(QueryData)]
+pub struct ParseOptions {
+    forward: Has<ForwardExternalCalls>,
+    seed: Option<&'static InkSeed>,
+    init_vars: Option<&'static InkInitVars>,
+    preset: Option<&'static InkVarsPreset>,
+    variables: Option<&'static InkVariables>,
+}
+
+impl ParseOptionsItem<'_> {
+    /// Sets up a freshly parsed story, and if it starts from the beginning
+    /// rather than a kept state, its starting variables and seed too.
+    fn apply(
+        &self,
+        ink_stories: &mut InkStories,
+        presets: &Assets<InkVarsAsset>,
+        id: Entity,
+        from_start: bool,
+    ) {
+        if self.forward
+            && let Err(err) = ink_stories.forward_external_calls(id)
+        {
+            warn!("Could not forward ink external calls in {id}: {err}");
+        }
+        if !from_start {
+            return;
+        }
+        if let Some(InkVarsPreset(handle)) = self.preset
+            && let Some(InkVarsAsset(vars)) = presets.get(handle)
+        {
+            for (name, value) in vars {
+                if let Err(err) = ink_stories.set_variable(id, name, value) {
+                    warn!("Could not set ink variable {name:?} in {id}: {err}");
+                }
+            }
+        }
+        if let Some(InkInitVars(vars)) = self.init_vars {
+            for (name, value) in vars {
+                if let Err(err) = ink_stories.set_variable(id, name, value) {
+                    warn!("Could not set ink variable {name:?} in {id}: {err}");
+                }
+            }
+        }
+        if let Some(values) = self.variables.and_then(InkVariables::initial_values) {
+            for (name, value) in values {
+                if let Err(err) = ink_stories.set_variable(id, name, value) {
+                    warn!("Could not set ink variable {name:?} in {id}: {err}");
+                }
+            }
+        }
+        if let Some(InkSeed(seed)) = self.seed
+            && let Err(err) = ink_stories.set_seed(id, *seed)
+        {
+            warn!("Could not seed ink in {id}: {err}");
+        }
+    }
+}
 
+/// The components that change how a story entity is hot reloaded.
+#[derive(QueryData)]
+pub struct ReloadOptions {
+    skip: Has<NoHotReload>,
+    defer: Has<DeferReload>,
+    inherit: Has<InheritVariables>,
+    preserve: Has<PreserveState>,
+    parse: ParseOptions,
+}
+
+#[allow(clippy::too_many_arguments)]
 fn hot_reload_on_modify(
     ink_texts: Res<Assets<InkText>>,
+    presets: Res<Assets<InkVarsAsset>>,
     mut events: EventReader<AssetEvent<InkText>>,
     mut ink_stories: NonSendMut<InkStories>,
     // We need to re-fetch the handle while pending.
-    ink_loads: Query<(Entity, &InkLoad)>,
+    ink_loads: Query<(Entity, &InkLoad, ReloadOptions)>,
     mut writer: EventWriter<InkEvent>,
+    mut commands: Commands,
+    settings: Res<InkSettings>,
+    tag_lint: Option<Res<TagLint>>,
+    time: Res<Time>,
+    // When each modified asset was last modified, waiting out the debounce.
+    mut modified: Local<HashMap<AssetId<InkText>, Duration>>,
+    // Entities whose reload is held off by DeferReload.
+    mut deferred: Local<HashSet<Entity>>,
 ) {
-    // For each modified asset, rebuild the runtime for all referencing entities.
+    // Reloads asked for in code go ahead even with hot reloading off.
+    for (entity, ink_text) in std::mem::take(&mut ink_stories.source_reloads) {
+        let options = ink_loads.get(entity).ok().map(|(_, _, options)| options);
+        reload_story(
+            &mut ink_stories,
+            entity,
+            &ink_text,
+            options.as_ref(),
+            &presets,
+            tag_lint.as_deref(),
+            &mut commands,
+            &mut writer,
+        );
+    }
+    if !settings.hot_reload {
+        events.clear();
+        modified.clear();
+        deferred.clear();
+        return;
+    }
     for ev in events.read() {
-        let asset_id = match ev {
-            AssetEvent::Modified { id } => *id,
+        match ev {
+            AssetEvent::Modified { id } => {
+                modified.insert(*id, time.elapsed());
+            }
             AssetEvent::Removed { id: _ } => {
                 // Optional: handle removal (e.g. remove InkRuntime from entities)
                 // *id
-                continue;
             }
-            _ => continue,
-        };
-        for (entity, ink) in &ink_loads {
-            if ink.0.id() != asset_id {
-                continue;
-            }
-            info!("reloading ink on {entity}");
-            if let Some(ink_text) = ink_texts.get(&ink.0) {
-                match ink_stories.try_parse(entity, ink_text) {
-                    Ok(_last_story) => {
-                        writer.write(InkEvent::OnStoryReload(entity));
-                    }
-                    Err(err) => {
-                        error!("Error parsing ink reload in {entity}: {err}");
-                    }
+            _ => {}
+        }
+    }
+
+    let now = time.elapsed();
+    let settled: HashSet<AssetId<InkText>> = modified
+        .iter()
+        .filter(|(_, at)| now.saturating_sub(**at) >= settings.reload_debounce)
+        .map(|(id, _)| *id)
+        .collect();
+    modified.retain(|id, _| !settled.contains(id));
+
+    // Rebuild the runtime for all entities referencing a settled asset or
+    // no longer deferring a reload, in entity order so their events come in
+    // the same order every run.
+    let mut reloads = Vec::new();
+    for (entity, ink, options) in &ink_loads {
+        if !(settled.contains(&ink.0.id()) || deferred.contains(&entity)) {
+            continue;
+        }
+        if options.skip {
+            deferred.remove(&entity);
+        } else if options.defer {
+            deferred.insert(entity);
+        } else {
+            deferred.remove(&entity);
+            reloads.push((entity, ink, options));
+        }
+    }
+    deferred.retain(|entity| ink_loads.contains(*entity));
+    reloads.sort_by_key(|(entity, ..)| *entity);
+    for (entity, ink, options) in reloads {
+        info!("reloading ink on {entity}");
+        if let Some(ink_text) = ink_texts.get(&ink.0) {
+            reload_story(
+                &mut ink_stories,
+                entity,
+                ink_text,
+                Some(&options),
+                &presets,
+                tag_lint.as_deref(),
+                &mut commands,
+                &mut writer,
+            );
+        }
+    }
+}
+
+/// Swaps in the story parsed from `ink_text` for `entity`, keeping its state
+/// or variables as its `options` say, and raises [InkEvent::OnStoryReload].
+#[allow(clippy::too_many_arguments)]
+fn reload_story(
+    ink_stories: &mut InkStories,
+    entity: Entity,
+    ink_text: &InkText,
+    options: Option<&ReloadOptionsItem>,
+    presets: &Assets<InkVarsAsset>,
+    tag_lint: Option<&TagLint>,
+    commands: &mut Commands,
+    writer: &mut EventWriter<InkEvent>,
+) {
+    let last_line = ink_stories.current_text(entity).ok();
+    let last_buffer = ink_stories.current_buffered_text(entity).ok();
+    let last_story = match ink_stories.try_parse(entity, ink_text) {
+        Ok(last_story) => last_story,
+        Err(err) => {
+            error!("Error parsing ink reload in {entity}: {err}");
+            writer.write(InkEvent::parse_error(entity, err.into()));
+            return;
+        }
+    };
+    let mut preserved = false;
+    if options.is_some_and(|options| options.preserve)
+        && let Some(last_story) = &last_story
+    {
+        match ink_stories.preserve_state(entity, last_story) {
+            Ok(()) => {
+                preserved = true;
+                if let Ok(entry) = ink_stories.entry_mut(entity) {
+                    entry.current_line = last_line;
+                    entry.buffered_text = last_buffer.unwrap_or_default();
                 }
             }
+            Err(err) => {
+                warn!("Could not keep ink state on reload in {entity}, starting over: {err}");
+            }
         }
     }
+    if let Some(options) = options {
+        options
+            .parse
+            .apply(ink_stories, presets, entity, !preserved);
+    }
+    if options.is_some_and(|options| options.inherit)
+        && !preserved
+        && let Some(last_story) = &last_story
+        && let Err(err) = ink_stories.inherit_variables(entity, last_story)
+    {
+        warn!("Could not keep variables on ink reload in {entity}: {err}");
+    }
+    ink_stories.raise_tag_lint(entity, tag_lint);
+    let meta = ink_stories.meta(entity).unwrap_or_default();
+    let tags_changed = last_story
+        .as_ref()
+        .is_none_or(|last_story| last_story.get_global_tags().ok().as_ref() != Some(&meta.tags));
+    if let Ok(mut entity) = commands.get_entity(entity) {
+        entity.insert(meta);
+    }
+    writer.write(InkEvent::OnStoryReload {
+        entity,
+        preserved,
+        tags_changed,
+    });
 }
+
+#[allow(clippy::too_many_arguments)]
 pub fn load_on_add_then_poll(
     ink_texts: Res<Assets<InkText>>,
+    presets: Res<Assets<InkVarsAsset>>,
+    asset_server: Res<AssetServer>,
+    settings: Res<InkSettings>,
+    tag_lint: Option<Res<TagLint>>,
+    time: Res<Time>,
     mut commands: Commands,
-    // Track only entities that *just gained* InkStory.
-    added: Query<(Entity, &InkLoad), Added<InkLoad>>,
+    // Track entities that just gained InkLoad or had it changed.
+    added: Query<(Entity, Ref<InkLoad>), Changed<InkLoad>>,
     mut ink_stories: NonSendMut<InkStories>,
     // We need to re-fetch the handle while pending.
-    stories: Query<&InkLoad>,
-    // Local set of entities waiting for their asset to become available.
-    mut pending: Local<HashSet<Entity>>,
+    stories: Query<(&InkLoad, ReloadOptions, Option<&InkRestore>)>,
+    // Local map of entities waiting for their asset to become available to
+    // when they started waiting.
+    mut pending: Local<HashMap<Entity, Duration>>,
+    // Local map of entities to the asset they last started loading.
+    mut loaded: Local<HashMap<Entity, AssetId<InkText>>>,
 ) {
-    // Start tracking newly-added stories.
-    for (e, _) in &added {
-        pending.insert(e);
+    let now = time.elapsed();
+    // Start tracking newly-added stories and swapped assets.
+    for (e, load) in &added {
+        // Mutable access marks InkLoad changed even when its handle stays the
+        // same, and that asset's own changes are left to hot reloading.
+        if loaded.insert(e, load.0.id()) == Some(load.0.id()) && !load.is_added() {
+            continue;
+        }
+        pending.insert(e, now);
+        commands.entity(e).insert(InkLoadState::Loading);
+    }
+    if !added.is_empty() {
+        loaded.retain(|&e, _| stories.contains(e));
     }
 
     if pending.is_empty() {
         return;
     }
 
+    // Drop the templates of assets that are gone.
+    ink_stories
+        .templates
+        .retain(|asset, _| ink_texts.contains(*asset));
+    let mut budget = settings.parses_per_frame.max(1);
+
     // Poll pending entities; stop tracking when resolved.
-    pending.retain(|&e| {
-        let Ok(story) = stories.get(e) else {
+    pending.retain(|&e, &mut since| {
+        let Ok((story, reload, restore)) = stories.get(e) else {
             // Entity despawned or component removed.
             return false;
         };
+        let options = &reload.parse;
+
+        let path = || {
+            story
+                .0
+                .path()
+                .map(|path| path.to_string())
+                .unwrap_or_default()
+        };
+        if let Some(LoadState::Failed(err)) = asset_server.get_load_state(&story.0) {
+            let path = path();
+            error!("Could not load ink {path:?} in {e}: {err}");
+            commands
+                .entity(e)
+                .insert(InkLoadState::Failed(err.to_string()));
+            ink_stories
+                .events
+                .push(InkEvent::LoadFailed { entity: e, path });
+            return false;
+        }
 
-        if let Some(ink) = ink_texts.get(&story.0) {
-            match ink_stories.try_parse(e, ink) {
-                Ok(_last_story) => {
-                    commands.entity(e).insert(InkStory);
+        // Wait for the entity's variable preset too, so it's set before the
+        // story gets InkStory.
+        let preset = options.preset.map(|InkVarsPreset(handle)| handle);
+        if let Some(handle) = preset
+            && let Some(LoadState::Failed(err)) = asset_server.get_load_state(handle)
+        {
+            let path = handle
+                .path()
+                .map(|path| path.to_string())
+                .unwrap_or_default();
+            error!("Could not load ink variable preset {path:?} in {e}: {err}");
+            commands
+                .entity(e)
+                .insert(InkLoadState::Failed(err.to_string()));
+            ink_stories
+                .events
+                .push(InkEvent::LoadFailed { entity: e, path });
+            return false;
+        }
+        let preset_ready = preset.is_none_or(|handle| presets.contains(handle));
+
+        if preset_ready && let Some(ink) = ink_texts.get(&story.0) {
+            if budget == 0 {
+                return true; // Parse next frame.
+            }
+            budget -= 1;
+            match ink_stories.parse_asset(e, story.0.id(), ink) {
+                Ok(last_story) => {
+                    // A swapped asset may carry over the old story's state.
+                    let preserved = reload.preserve
+                        && last_story.as_ref().is_some_and(|last_story| {
+                            ink_stories
+                                .preserve_state(e, last_story)
+                                .inspect_err(|err| {
+                                    warn!("Could not keep ink state in {e}, starting over: {err}")
+                                })
+                                .is_ok()
+                        });
+                    options.apply(&mut ink_stories, &presets, e, !preserved);
+                    if reload.inherit
+                        && !preserved
+                        && let Some(last_story) = &last_story
+                        && let Err(err) = ink_stories.inherit_variables(e, last_story)
+                    {
+                        warn!("Could not keep ink variables in {e}: {err}");
+                    }
+                    if let Some(InkRestore(state)) = restore {
+                        ink_stories.restore(e, state, options, &presets);
+                        commands.entity(e).remove::<InkRestore>();
+                    }
+                    ink_stories.raise_tag_lint(e, tag_lint.as_deref());
+                    let meta = ink_stories.meta(e).unwrap_or_default();
+                    ink_stories
+                        .events
+                        .push(InkEvent::OnStoryLoaded { entity: e });
+                    commands
+                        .entity(e)
+                        .insert((InkStory, InkLoadState::Ready, meta));
                 }
                 Err(err) => {
                     error!("Error parsing ink in {e}: {err}");
+                    let message = err.to_string();
+                    ink_stories.events.push(InkEvent::parse_error(e, err));
+                    commands.entity(e).insert(InkLoadState::Failed(message));
                 }
             }
             false // Remove from pending. Stop waiting.
+        } else if let Some(timeout) = settings.load_timeout
+            && now.saturating_sub(since) >= timeout
+        {
+            let path = path();
+            let message = format!("timed out after {timeout:?}");
+            error!("Could not load ink {path:?} in {e}: {message}");
+            commands.entity(e).insert(InkLoadState::Failed(message));
+            ink_stories
+                .events
+                .push(InkEvent::LoadFailed { entity: e, path });
+            false
         } else {
             true // Keep waiting.
         }
@@ -164,19 +1852,49 @@ pub enum InkProcessor {
 
 impl InkProcessor {
     pub fn command(&self) -> Command {
+        self.command_with_args(&[])
+    }
+
+    /// Like [InkProcessor::command] with `flags` ahead of the processor's own
+    /// arguments, e.g., inklecate's `-c` to count all visits.
+    pub fn command_with_args(&self, flags: &[String]) -> Command {
         match self {
             InkProcessor::Inklecate => {
                 let mut c = Command::new("inklecate");
+                c.args(flags);
                 c.args(["-o", "/dev/stdout", "/dev/stdin"]);
                 c
             }
             InkProcessor::Custom { command, arguments } => {
                 let mut c = Command::new(command);
+                c.args(flags);
                 c.args(arguments);
                 c
             }
         }
     }
+
+    /// Compiles ink source into JSON, giving up after
+    /// [DEFAULT_COMPILE_TIMEOUT].
+    pub fn compile(&self, source: &[u8]) -> Result<String, InkError> {
+        self.compile_with_args(source, &[], DEFAULT_COMPILE_TIMEOUT)
+    }
+
+    /// Compiles ink source into JSON, passing `flags` to the processor. The
+    /// processor is killed if it runs longer than `timeout`.
+    pub fn compile_with_args(
+        &self,
+        source: &[u8],
+        flags: &[String],
+        timeout: Duration,
+    ) -> Result<String, InkError> {
+        let output = run_with_timeout(
+            self.command_with_args(flags).stdin(Stdio::piped()),
+            Some(source.to_vec()),
+            timeout,
+        )?;
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
     // pub fn command(&self) -> &str {
     //     match self {
     //         InkProcessor::Inklecate => "inklecate",
@@ -192,21 +1910,237 @@ impl InkProcessor {
     // }
 }
 
+/// How long a processor may take to compile before it's killed, unless
+/// [LoaderSettings::compile_timeout] says otherwise.
+pub const DEFAULT_COMPILE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Runs `command` with `input` on its stdin and returns its output, killing
+/// it if it doesn't finish within `timeout`, e.g., when it hangs on bad input.
+/// Errors with its stderr if it exits unsuccessfully.
+fn run_with_timeout(
+    command: &mut Command,
+    input: Option<Vec<u8>>,
+    timeout: Duration,
+) -> std::io::Result<Output> {
+    let mut child = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    // Feed stdin and drain stdout and stderr on their own threads, so none can
+    // block while a full pipe waits on another.
+    if let (Some(mut stdin), Some(input)) = (child.stdin.take(), input) {
+        std::thread::spawn(move || stdin.write_all(&input));
+    }
+    fn drain(pipe: Option<impl Read + Send + 'static>) -> std::thread::JoinHandle<Vec<u8>> {
+        std::thread::spawn(move || {
+            let mut bytes = Vec::new();
+            if let Some(mut pipe) = pipe {
+                let _ = pipe.read_to_end(&mut bytes);
+            }
+            bytes
+        })
+    }
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+    let started = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait()? {
+            let output = Output {
+                status,
+                stdout: stdout.join().unwrap_or_default(),
+                stderr: stderr.join().unwrap_or_default(),
+            };
+            if !status.success() {
+                return Err(std::io::Error::other(format!(
+                    "ink processor failed ({status}): {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                )));
+            }
+            return Ok(output);
+        }
+        if started.elapsed() >= timeout {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!("ink processor didn't finish within {timeout:?} and was killed"),
+            ));
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+fn default_compile_timeout() -> Duration {
+    DEFAULT_COMPILE_TIMEOUT
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct LoaderSettings {
     pub processor: Option<InkProcessor>,
+    /// Overrides detecting the format from the asset's extension, e.g., for
+    /// in-memory sources without a meaningful path.
+    pub format: Option<InkFormat>,
+    /// Extra flags for the processor when compiling source, put ahead of its
+    /// own arguments.
+    #[serde(default)]
+    pub processor_args: Vec<String>,
+    /// Drops everything but what the runtime reads from the compiled JSON and
+    /// compacts it, which makes shipped stories smaller and quicker to parse.
+    #[serde(default)]
+    pub strip_debug: bool,
+    /// How long compiling source may take before the processor is killed and
+    /// the load fails, so a hung processor can't wedge asset loading.
+    #[serde(default = "default_compile_timeout")]
+    pub compile_timeout: Duration,
+    /// Compiles source with inklecate's `-c`, so ink counts visits to every
+    /// knot, stitch, and gather rather than only those the story reads the
+    /// count of. Without it, [InkStories::visit_count] and
+    /// [InkStories::is_first_visit], like `TURNS_SINCE()` and `READ_COUNT()`
+    /// on a divert target variable, only know the counts ink chose to keep.
+    #[serde(default)]
+    pub count_all_visits: bool,
+    /// Where the assets are on disk, e.g., `"assets"`, to load a `.ink` from
+    /// the `.ink.json` beside it instead of compiling while the JSON is newer
+    /// than the source and everything it includes. `None` always compiles.
+    ///
+    /// The JSON is taken as it is, so it should have been compiled with the
+    /// same flags as these settings ask for.
+    #[serde(default)]
+    pub compiled_cache: Option<std::path::PathBuf>,
+}
+
+#[cfg(feature = "inklecate")]
+impl LoaderSettings {
+    /// The flags to compile with: [LoaderSettings::processor_args] and those
+    /// the other settings ask for.
+    fn flags(&self) -> Vec<String> {
+        let mut flags = self.processor_args.clone();
+        if self.count_all_visits && !flags.iter().any(|flag| flag == "-c") {
+            flags.push("-c".to_string());
+        }
+        flags
+    }
 }
 
 impl Default for LoaderSettings {
     fn default() -> Self {
         Self {
             processor: Some(InkProcessor::default()),
+            format: None,
+            processor_args: Vec::new(),
+            strip_debug: false,
+            compile_timeout: DEFAULT_COMPILE_TIMEOUT,
+            count_all_visits: false,
+            compiled_cache: None,
+        }
+    }
+}
+
+/// Loads [InkText] from any asset source, e.g., an HTTP one for ink updated
+/// over the air. A URL's query and fragment, as in `intro.ink?v=3`, are
+/// ignored when telling source from compiled JSON by the extension. Remote
+/// source is compiled here like a local file, and fails to load with
+/// [InkError::NoProcessor] if [LoaderSettings::processor] is `None`, so a
+/// build without a compiler should fetch compiled `.ink.json` instead.
+pub struct InkTextLoader {
+    /// Permits for the compiles that may run at once.
+    #[cfg(feature = "inklecate")]
+    compiles: Semaphore,
+    extensions: Vec<&'static str>,
+}
+
+impl InkTextLoader {
+    /// Makes a loader that compiles at most `max_parallel_compiles` sources
+    /// at once.
+    pub fn new(max_parallel_compiles: usize) -> Self {
+        #[cfg(not(feature = "inklecate"))]
+        let _ = max_parallel_compiles;
+        Self {
+            #[cfg(feature = "inklecate")]
+            compiles: Semaphore::new(max_parallel_compiles.max(1)),
+            extensions: EXTENSIONS.to_vec(),
+        }
+    }
+
+    /// Also loads files with these extensions, as compiled JSON unless they
+    /// end in `ink`.
+    pub fn with_extensions(mut self, extensions: &[&'static str]) -> Self {
+        for extension in extensions {
+            if !self.extensions.contains(extension) {
+                self.extensions.push(extension);
+            }
+        }
+        self
+    }
+}
+
+impl InkTextLoader {
+    /// Compiles `bytes` if they're source or takes them as they are.
+    async fn read(
+        &self,
+        bytes: &[u8],
+        format: InkFormat,
+        settings: &LoaderSettings,
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<InkText, InkError> {
+        #[cfg(feature = "inklecate")]
+        if format == InkFormat::Source {
+            let processor = settings.processor.as_ref().ok_or(InkError::NoProcessor)?;
+            let flags = &settings.flags();
+            let includes = include::read_includes(bytes, load_context).await?;
+            if let Some(root) = &settings.compiled_cache
+                && let Some(json) = cache::fresh_json(root, load_context.path(), &includes)
+            {
+                return Ok(InkText(json, InkFormat::Source));
+            }
+            // Wait without blocking the thread, which other loads share.
+            let _permit = self.compiles.acquire().await;
+            if includes.is_empty() {
+                return processor
+                    .compile_with_args(bytes, flags, settings.compile_timeout)
+                    .map(|json| InkText(json, InkFormat::Source));
+            }
+            let name = file_name(load_context.path())
+                .unwrap_or("main.ink")
+                .to_string();
+            return processor
+                .compile_with_includes(&name, bytes, &includes, flags, settings.compile_timeout)
+                .map(|json| InkText(json, InkFormat::Source));
         }
+
+        #[cfg(not(feature = "inklecate"))]
+        let _ = load_context;
+        InkText::from_bytes(bytes, format, settings.processor.as_ref())
     }
 }
 
-#[derive(Default)]
-pub struct InkTextLoader;
+/// Returns the file name of `path` without a URL's query or fragment.
+fn file_name(path: &Path) -> Option<&str> {
+    let name = path.file_name()?.to_str()?;
+    name.split(['?', '#'])
+        .next()
+        .filter(|name| !name.is_empty())
+}
+
+/// Returns the last extension of [file_name], e.g., `"ink"` for
+/// `intro.ink?v=3`.
+fn extension(path: &Path) -> Option<&str> {
+    file_name(path)?
+        .rsplit_once('.')
+        .map(|(_, extension)| extension)
+}
+
+impl Default for InkTextLoader {
+    fn default() -> Self {
+        Self::new(InkSettings::default().max_parallel_compiles)
+    }
+}
+
+/// The extensions [InkTextLoader] loads.
+#[cfg(not(feature = "gzip"))]
+const EXTENSIONS: &[&str] = &["ink.json", "ink"];
+#[cfg(feature = "gzip")]
+const EXTENSIONS: &[&str] = &["ink.json", "ink", "ink.json.gz"];
 
 impl AssetLoader for InkTextLoader {
     type Asset = InkText;
@@ -214,7 +2148,7 @@ impl AssetLoader for InkTextLoader {
     type Error = InkError;
 
     fn extensions(&self) -> &[&str] {
-        &["ink.json", "ink"]
+        &self.extensions
     }
 
     async fn load(
@@ -226,29 +2160,261 @@ impl AssetLoader for InkTextLoader {
         let mut bytes = Vec::new();
         reader.read_to_end(&mut bytes).await?;
 
-        // Check if the file extension is "ink" and compile it with inklecate
-        let path = load_context.path();
-        let extension = path.extension().and_then(|ext| ext.to_str());
+        #[cfg(feature = "gzip")]
+        if extension(load_context.path()) == Some("gz") {
+            let mut json = Vec::new();
+            flate2::read::GzDecoder::new(&bytes[..]).read_to_end(&mut json)?;
+            bytes = json;
+        }
+
+        // `.ink` is source to compile; anything else is taken as compiled JSON.
+        let format = settings
+            .format
+            .unwrap_or_else(|| match extension(load_context.path()) {
+                Some("ink") => InkFormat::Source,
+                _ => InkFormat::Json,
+            });
+
+        let text = self.read(&bytes, format, settings, load_context).await?;
+        if !settings.strip_debug {
+            return Ok(text);
+        }
+        let format = text.1;
+        Ok(compiled::strip_debug(&text.0)
+            .map(|json| InkText(json, format))
+            .unwrap_or(text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::time::TimeUpdateStrategy;
 
-        if extension == Some("ink") {
-            if let Some(processor) = &settings.processor {
-                let mut child = processor
-                    .command()
-                    .stdin(Stdio::piped())
-                    .stdout(Stdio::piped())
-                    .spawn()?;
+    const LINES: &str = include_str!("../assets/lines.ink.json");
+    const BROKEN_DIVERT: &str = include_str!("../assets/broken_divert.ink.json");
+    const CHOICES: &str = include_str!("../assets/choices.ink.json");
+    const MISSING_VARIABLE: &str = include_str!("../assets/missing_variable.ink.json");
 
-                child.stdin.as_mut().unwrap().write_all(&bytes)?;
+    /// An app with [InkPlugin] and what it needs to run headless.
+    pub(crate) fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default(), InkPlugin));
+        #[cfg(feature = "scripting")]
+        app.add_plugins((
+            bevy::diagnostic::DiagnosticsPlugin,
+            bevy_mod_scripting::BMSPlugin,
+        ));
+        app
+    }
 
-                let output = child.wait_with_output()?;
-                let compiled_json = String::from_utf8_lossy(&output.stdout);
+    #[test]
+    fn current_text_rereads_the_last_line() {
+        let mut ink_stories = InkStories::default();
+        let id = Entity::from_raw(0);
+        ink_stories.parse_from_str(id, LINES).unwrap();
+        assert_eq!(ink_stories.cont(id).unwrap(), "Hello\n");
+        assert_eq!(ink_stories.current_text(id).unwrap(), "Hello\n");
+        assert_eq!(ink_stories.current_text(id).unwrap(), "Hello\n");
+        assert_eq!(ink_stories.cont(id).unwrap(), "World\n");
+    }
 
-                Ok(InkText(compiled_json.into_owned()))
-            } else {
-                Err(InkError::NoProcessor)
-            }
-        } else {
-            Ok(InkText(String::from_utf8_lossy(&bytes).into()))
+    #[test]
+    fn buffered_text_holds_the_block_before_the_choices() {
+        let mut ink_stories = InkStories::default();
+        let id = Entity::from_raw(0);
+        ink_stories.parse_from_str(id, CHOICES).unwrap();
+        ink_stories.cont(id).unwrap();
+        ink_stories.cont(id).unwrap();
+        assert_eq!(
+            ink_stories.current_buffered_text(id).unwrap(),
+            "Hello.\nHow are you?\n"
+        );
+        assert_eq!(ink_stories.current_text(id).unwrap(), "How are you?\n");
+
+        ink_stories.choose_choice_index(id, 1).unwrap();
+        ink_stories.cont(id).unwrap();
+        assert_eq!(
+            ink_stories.current_buffered_text(id).unwrap(),
+            "Get some rest.\n"
+        );
+    }
+
+    #[test]
+    fn warnings_are_raised_without_failing_the_line() {
+        // The story reads `gold`, which it never declares, as JSON compiled
+        // against an older version of its variables would.
+        let mut ink_stories = InkStories::default();
+        let id = Entity::from_raw(0);
+        ink_stories.parse_from_str(id, MISSING_VARIABLE).unwrap();
+        ink_stories.take_events();
+        assert!(ink_stories.cont(id).unwrap().starts_with("Gold: "));
+        let warnings: Vec<_> = ink_stories
+            .take_events()
+            .into_iter()
+            .filter_map(|event| match event {
+                InkEvent::Warning { entity, message } if entity == id => Some(message),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(warnings.len(), 1, "{warnings:?}");
+        assert!(warnings[0].contains("gold"), "{warnings:?}");
+    }
+
+    #[test]
+    fn continue_error_policy_handles_a_broken_divert() {
+        let mut ink_stories = InkStories::default();
+        let id = Entity::from_raw(0);
+        ink_stories.parse_from_str(id, BROKEN_DIVERT).unwrap();
+        assert_eq!(ink_stories.cont(id).unwrap(), "Start\n");
+        assert!(ink_stories.cont(id).is_err());
+
+        ink_stories.parse_from_str(id, BROKEN_DIVERT).unwrap();
+        ink_stories
+            .set_continue_error_policy(id, ContinueErrorPolicy::Reset)
+            .unwrap();
+        // Reparsing, as a hot reload does, keeps the story's policy.
+        ink_stories.parse_from_str(id, BROKEN_DIVERT).unwrap();
+        assert_eq!(ink_stories.cont(id).unwrap(), "Start\n");
+        assert_eq!(ink_stories.cont(id).unwrap(), "");
+        assert_eq!(ink_stories.cont(id).unwrap(), "Start\n");
+    }
+
+    /// A processor that runs `script` with `sh`.
+    fn shell(script: &str) -> InkProcessor {
+        InkProcessor::Custom {
+            command: "sh".into(),
+            arguments: vec!["-c".into(), script.into()],
+        }
+    }
+
+    #[test]
+    fn a_hung_processor_is_killed() {
+        let started = Instant::now();
+        let result = shell("sleep 5").compile_with_args(b"", &[], Duration::from_millis(100));
+        assert!(matches!(
+            result,
+            Err(InkError::IoError(err)) if err.kind() == std::io::ErrorKind::TimedOut
+        ));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn a_failed_compile_errors_with_its_stderr() {
+        let result = shell("echo 'bad ink' >&2; exit 1").compile(b"");
+        let Err(InkError::IoError(err)) = result else {
+            panic!("compile didn't fail: {result:?}");
+        };
+        assert!(err.to_string().contains("bad ink"), "{err}");
+        assert_eq!(shell("cat").compile(LINES.as_bytes()).unwrap(), LINES);
+    }
+
+    #[test]
+    fn despawned_story_entities_are_pruned() {
+        let mut app = app();
+        app.world_mut()
+            .resource_mut::<InkSettings>()
+            .parses_per_frame = 1000;
+        let handle = app
+            .world_mut()
+            .resource_mut::<Assets<InkText>>()
+            .add(InkText(LINES.into(), InkFormat::Json));
+        let ids: Vec<Entity> = (0..1000)
+            .map(|_| app.world_mut().spawn(InkLoad(handle.clone())).id())
+            .collect();
+        app.update();
+        app.update();
+        assert_eq!(app.world().non_send_resource::<InkStories>().len(), 1000);
+
+        for id in ids {
+            app.world_mut().despawn(id);
+        }
+        app.update();
+        assert!(app.world().non_send_resource::<InkStories>().is_empty());
+    }
+
+    #[test]
+    fn entities_sharing_an_asset_share_its_json() {
+        let mut app = app();
+        let handle = app
+            .world_mut()
+            .resource_mut::<Assets<InkText>>()
+            .add(InkText(LINES.into(), InkFormat::Json));
+        let ids: Vec<Entity> = (0..3)
+            .map(|_| app.world_mut().spawn(InkLoad(handle.clone())).id())
+            .collect();
+        app.update();
+        app.update();
+
+        let mut ink_stories = app.world_mut().non_send_resource_mut::<InkStories>();
+        assert_eq!(ink_stories.templates.len(), 1);
+        let template = &ink_stories.templates[&handle.id()];
+        // The template and each of the three stories hold the one copy.
+        assert_eq!(Rc::strong_count(&template.json), 4);
+        for id in ids {
+            assert_eq!(ink_stories.cont(id).unwrap(), "Hello\n");
+        }
+    }
+
+    #[test]
+    fn an_asset_that_fails_to_parse_is_parsed_once() {
+        let mut app = app();
+        let handle = app
+            .world_mut()
+            .resource_mut::<Assets<InkText>>()
+            .add(InkText("not ink".into(), InkFormat::Json));
+        for _ in 0..3 {
+            app.world_mut().spawn(InkLoad(handle.clone()));
+        }
+        app.update();
+        app.update();
+        assert_eq!(app.world().non_send_resource::<InkStories>().parses, 1);
+        assert!(app.world().non_send_resource::<InkStories>().is_empty());
+
+        // Fixing the asset drops the kept failure.
+        let mut ink_stories = app.world_mut().non_send_resource_mut::<InkStories>();
+        let fixed = InkText(LINES.into(), InkFormat::Json);
+        let id = Entity::from_raw(0);
+        ink_stories.parse_asset(id, handle.id(), &fixed).unwrap();
+        assert_eq!(ink_stories.parses, 2);
+        assert_eq!(ink_stories.cont(id).unwrap(), "Hello\n");
+    }
+
+    #[test]
+    fn hot_reload_waits_out_the_debounce_on_the_virtual_clock() {
+        let mut app = app();
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            100,
+        )));
+        let handle = app
+            .world_mut()
+            .resource_mut::<Assets<InkText>>()
+            .add(InkText(LINES.into(), InkFormat::Json));
+        let id = app.world_mut().spawn(InkLoad(handle.clone())).id();
+        app.update();
+        app.update();
+        let text = |app: &App| {
+            app.world()
+                .non_send_resource::<InkStories>()
+                .current_text(id)
+                .unwrap_or_default()
+        };
+        app.world_mut()
+            .non_send_resource_mut::<InkStories>()
+            .cont(id)
+            .unwrap();
+
+        app.world_mut()
+            .resource_mut::<Assets<InkText>>()
+            .get_mut(&handle)
+            .unwrap();
+        // The modification reaches the reload on the second update, and as
+        // each update adds 100 ms, the 250 ms debounce ends three after that.
+        for _ in 0..4 {
+            app.update();
         }
+        assert_eq!(text(&app), "Hello\n");
+        app.update();
+        assert_eq!(text(&app), "");
     }
 }
@@ -0,0 +1,129 @@
+use super::*;
+use bladeink::value_type::ValueType;
+
+/// Whether `full_name`, e.g., `"Colors.red"`, names `item`, which may be full
+/// or bare like `"red"`.
+fn names_item(full_name: &str, item: &str) -> bool {
+    full_name == item
+        || full_name
+            .split_once('.')
+            .is_some_and(|(_, bare)| bare == item)
+}
+
+impl InkStories {
+    /// Returns the full name of the list variable `name`'s item with the
+    /// lowest value, like ink's `LIST_MIN(name)`, or `None` if it's empty.
+    pub fn list_min(&self, id: Entity, name: &str) -> Result<Option<String>, InkError> {
+        // Items come ordered by value.
+        Ok(self.list_items(id, name)?.into_iter().next())
+    }
+
+    /// Returns the full name of the list variable `name`'s item with the
+    /// highest value, like ink's `LIST_MAX(name)`, or `None` if it's empty.
+    pub fn list_max(&self, id: Entity, name: &str) -> Result<Option<String>, InkError> {
+        Ok(self.list_items(id, name)?.pop())
+    }
+
+    /// Returns the values of the list variables `a` and `b`.
+    fn list_values(
+        &self,
+        id: Entity,
+        a: &str,
+        b: &str,
+    ) -> Result<(ValueType, ValueType), InkError> {
+        let story = self.get(id)?;
+        let value = |name: &str| match story.get_variable(name) {
+            Some(value @ ValueType::List(_)) => Ok(value),
+            Some(_) => Err(InkError::NotAList(name.to_string())),
+            None => Err(InkError::NoSuchVariable(name.to_string())),
+        };
+        Ok((value(a)?, value(b)?))
+    }
+
+    /// Whether the list variable `a` is above `b`, like ink's `a > b`: every
+    /// item of `a` has a higher value than every item of `b`. As in ink, this
+    /// isn't a total order, so `a` may be neither above nor below `b`.
+    pub fn list_greater_than(&self, id: Entity, a: &str, b: &str) -> Result<bool, InkError> {
+        match self.list_values(id, a, b)? {
+            (ValueType::List(a), ValueType::List(b)) => Ok(a.greater_than(&b)),
+            _ => unreachable!("list_values returns only lists"),
+        }
+    }
+
+    /// Whether the list variable `a` is below `b`, like ink's `a < b`; see
+    /// [InkStories::list_greater_than].
+    pub fn list_less_than(&self, id: Entity, a: &str, b: &str) -> Result<bool, InkError> {
+        match self.list_values(id, a, b)? {
+            (ValueType::List(a), ValueType::List(b)) => Ok(a.less_than(&b)),
+            _ => unreachable!("list_values returns only lists"),
+        }
+    }
+
+    /// Returns the full names of the items in the list variable `name`.
+    pub fn list_items(&self, id: Entity, name: &str) -> Result<Vec<String>, InkError> {
+        let value = self
+            .get(id)?
+            .get_variable(name)
+            .ok_or_else(|| InkError::NoSuchVariable(name.to_string()))?;
+        match VariableValue::from_value_type(&value) {
+            Some(VariableValue::List(items)) => Ok(items),
+            _ => Err(InkError::NotAList(name.to_string())),
+        }
+    }
+
+    /// Returns the full names of every item the list variable `name` could
+    /// hold, held or not, ordered by value like ink's `LIST_ALL(name)`, e.g.,
+    /// to grey out the missing ones in an inventory grid. A `LIST` declares a
+    /// variable of its own name, so `name` may be the list's. A variable
+    /// holding items from several lists gets every item of all of them,
+    /// ordered by value across lists.
+    pub fn list_definition(&self, id: Entity, name: &str) -> Result<Vec<String>, InkError> {
+        let value = self
+            .get(id)?
+            .get_variable(name)
+            .ok_or_else(|| InkError::NoSuchVariable(name.to_string()))?;
+        let ValueType::List(list) = value else {
+            return Err(InkError::NotAList(name.to_string()));
+        };
+        let all = ValueType::List(list.union(&list.inverse()));
+        match VariableValue::from_value_type(&all) {
+            Some(VariableValue::List(items)) => Ok(items),
+            _ => unreachable!("a list's items are a list"),
+        }
+    }
+
+    /// Whether the list variable `name` holds `item`, named in full like
+    /// `"Colors.red"` or bare like `"red"`.
+    pub fn list_contains(&self, id: Entity, name: &str, item: &str) -> Result<bool, InkError> {
+        Ok(self
+            .list_items(id, name)?
+            .iter()
+            .any(|full_name| names_item(full_name, item)))
+    }
+
+    /// Adds `item` to the list variable `name`, like ink's `name += item`.
+    ///
+    /// Errors with [InkError::NoSuchListItem] if no list defines `item`.
+    pub fn list_add(&mut self, id: Entity, name: &str, item: &str) -> Result<(), InkError> {
+        let mut items = self.list_items(id, name)?;
+        if items.iter().any(|full_name| names_item(full_name, item)) {
+            return Ok(());
+        }
+        items.push(item.to_string());
+        self.set_variable(id, name, &VariableValue::List(items))
+            .map(drop)
+    }
+
+    /// Removes `item` from the list variable `name`, like ink's `name -= item`.
+    /// Removing an item the list doesn't hold does nothing.
+    pub fn list_remove(&mut self, id: Entity, name: &str, item: &str) -> Result<(), InkError> {
+        let mut items = self.list_items(id, name)?;
+        let count = items.len();
+        items.retain(|full_name| !names_item(full_name, item));
+        if items.len() == count {
+            return Ok(());
+        }
+        self.set_variable(id, name, &VariableValue::List(items))
+            .map(drop)
+    }
+}
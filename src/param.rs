@@ -0,0 +1,63 @@
+use super::*;
+use bevy::ecs::system::SystemParam;
+use std::ops::{Deref, DerefMut};
+
+/// Add to a story entity to group it with others, e.g., `"quest"` or
+/// `"ambient"`, so [Ink::in_category] can save or reset only those.
+#[derive(Debug, Component, Clone, PartialEq, Eq, Hash)]
+pub struct StoryCategory(pub String);
+
+/// Story access for systems in one parameter: every [InkStories] method, such
+/// as `ink.cont(entity)`, through `Deref`, plus which story entities are
+/// ready. Like [InkStories] itself, it keeps the system on the main thread.
+#[derive(SystemParam)]
+pub struct Ink<'w, 's> {
+    stories: NonSendMut<'w, InkStories>,
+    ready: Query<'w, 's, Entity, With<InkStory>>,
+    categories: Query<'w, 's, (Entity, &'static StoryCategory), With<InkStory>>,
+}
+
+impl Ink<'_, '_> {
+    /// Whether the entity's story is parsed and set up; see [InkStory].
+    pub fn is_ready(&self, id: Entity) -> bool {
+        self.ready.contains(id)
+    }
+
+    /// The story entities that are ready.
+    pub fn ready(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.ready.iter()
+    }
+
+    /// The ready story entities whose [StoryCategory] is `category`. Collect
+    /// them first to change their stories, e.g., to reset every `"ambient"`
+    /// one.
+    pub fn in_category<'a>(&'a self, category: &'a str) -> impl Iterator<Item = Entity> + 'a {
+        self.categories
+            .iter()
+            .filter(move |(_, StoryCategory(name))| name == category)
+            .map(|(id, _)| id)
+    }
+
+    /// The stories of [Ink::in_category] with their entities.
+    pub fn stories_in_category<'a>(
+        &'a self,
+        category: &'a str,
+    ) -> impl Iterator<Item = (Entity, &'a Story)> + 'a {
+        self.in_category(category)
+            .filter_map(|id| Some((id, self.stories.get(id).ok()?)))
+    }
+}
+
+impl Deref for Ink<'_, '_> {
+    type Target = InkStories;
+
+    fn deref(&self) -> &InkStories {
+        &self.stories
+    }
+}
+
+impl DerefMut for Ink<'_, '_> {
+    fn deref_mut(&mut self) -> &mut InkStories {
+        &mut self.stories
+    }
+}
@@ -0,0 +1,68 @@
+use super::*;
+
+impl InkStories {
+    /// Parses compiled ink JSON and parks the story under `key` instead of on
+    /// an entity, e.g., to fill a pool of conversations for short-lived NPCs.
+    /// [InkStories::unpark] later moves it onto an entity without parsing
+    /// again. Returns the story parked under `key` before, if any.
+    pub fn park(&mut self, key: impl Into<String>, json: &str) -> Result<Option<Story>, InkError> {
+        let key = key.into();
+        let story = {
+            let _span = info_span!("ink_parse", key).entered();
+            Story::new(json)?
+        };
+        Ok(self
+            .parked
+            .insert(key, (story, json.into()))
+            .map(|(story, _)| story))
+    }
+
+    /// Moves the story parked under `key` onto `id`, which keeps the settings
+    /// its prior story had, as a reload does. Returns the prior story, if any.
+    /// A parked story is moved rather than copied, so park another to use
+    /// `key` again.
+    ///
+    /// This doesn't give the entity [InkStory]; see
+    /// [InkCommandsExt::unpark_ink].
+    pub fn unpark(&mut self, key: &str, id: Entity) -> Result<Option<Story>, InkError> {
+        let (story, json) = self
+            .parked
+            .remove(key)
+            .ok_or_else(|| InkError::NoParkedStory(key.to_string()))?;
+        Ok(self.insert(id, story, json, InkFormat::Json))
+    }
+
+    /// Whether a story is parked under `key`.
+    pub fn is_parked(&self, key: &str) -> bool {
+        self.parked.contains_key(key)
+    }
+
+    /// Drops the story parked under `key`, returning it if there was one.
+    pub fn discard_parked(&mut self, key: &str) -> Option<Story> {
+        self.parked.remove(key).map(|(story, _)| story)
+    }
+}
+
+/// Moves the story parked under `key` onto `id` as
+/// [InkCommandsExt::unpark_ink] does, giving it [InkStory] and raising
+/// [InkEvent::OnStoryLoaded].
+pub fn unpark_ink(world: &mut World, id: Entity, key: &str) -> Result<(), InkError> {
+    world
+        .non_send_resource_mut::<InkStories>()
+        .unpark(key, id)?;
+    mark_loaded(world, id);
+    Ok(())
+}
+
+/// Gives `id`, whose story was just put in [InkStories] without an asset,
+/// [InkStory] and its [InkMeta], and raises [InkEvent::OnStoryLoaded].
+pub(crate) fn mark_loaded(world: &mut World, id: Entity) {
+    let mut ink_stories = world.non_send_resource_mut::<InkStories>();
+    let meta = ink_stories.meta(id).unwrap_or_default();
+    ink_stories
+        .events
+        .push(InkEvent::OnStoryLoaded { entity: id });
+    if let Ok(mut entity) = world.get_entity_mut(id) {
+        entity.insert((InkStory, InkLoadState::Ready, meta));
+    }
+}
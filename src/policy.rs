@@ -0,0 +1,82 @@
+use super::*;
+
+/// Decides which choice to take when a story is played without a player,
+/// e.g., by an AI narrator or in automated tests.
+pub trait ChoicePolicy {
+    /// Returns the position in `choices` of the choice to take. `choices` is
+    /// never empty.
+    fn select(&mut self, choices: &[ChoiceInfo]) -> usize;
+}
+
+/// Always takes the first choice.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FirstChoice;
+
+impl ChoicePolicy for FirstChoice {
+    fn select(&mut self, _choices: &[ChoiceInfo]) -> usize {
+        0
+    }
+}
+
+/// Takes a pseudorandom choice. The same seed makes the same selections.
+#[derive(Debug, Clone, Copy)]
+pub struct RandomChoice(pub u64);
+
+impl ChoicePolicy for RandomChoice {
+    fn select(&mut self, choices: &[ChoiceInfo]) -> usize {
+        // splitmix64
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        (z % choices.len() as u64) as usize
+    }
+}
+
+/// Takes the first choice with this tag, or the first choice if none has it.
+#[derive(Debug, Clone)]
+pub struct TaggedChoice(pub String);
+
+impl ChoicePolicy for TaggedChoice {
+    fn select(&mut self, choices: &[ChoiceInfo]) -> usize {
+        choices
+            .iter()
+            .position(|choice| choice.tags.contains(&self.0))
+            .unwrap_or(0)
+    }
+}
+
+impl InkStories {
+    /// Plays the story until it ends, letting `policy` make every choice.
+    /// Returns the lines produced along the way.
+    ///
+    /// Gives up after [InkStories::max_continues] lines in all, as a story
+    /// can loop through its choices forever, and errors with
+    /// [InkError::ChoiceIndexOutOfRange] if `policy` selects past the choices.
+    pub fn run_to_end(
+        &mut self,
+        id: Entity,
+        policy: &mut impl ChoicePolicy,
+    ) -> Result<Vec<String>, InkError> {
+        let mut lines = Vec::new();
+        loop {
+            while self.get(id)?.can_continue() {
+                if lines.len() >= self.max_continues {
+                    return Err(InkError::ContinueLimitExceeded(lines.len()));
+                }
+                lines.push(self.cont(id)?);
+            }
+            let choices = self.current_choices(id)?;
+            if choices.is_empty() {
+                return Ok(lines);
+            }
+            let index = policy.select(&choices);
+            let choice = choices.get(index).ok_or(InkError::ChoiceIndexOutOfRange {
+                index,
+                available: choices.len(),
+            })?;
+            self.choose_choice_index(id, choice.index)?;
+        }
+    }
+}
@@ -0,0 +1,42 @@
+//! Compiles `.ink` sources to JSON in Bevy's asset processing pass, so a
+//! shipped game only ever loads compiled ink and never runs a processor.
+//!
+//! This runs when the app's `AssetPlugin` is in `AssetMode::Processed` with
+//! Bevy's `asset_processor` feature on.
+use super::*;
+use bevy::asset::{
+    AsyncWriteExt,
+    io::Writer,
+    processor::LoadTransformAndSave,
+    saver::{AssetSaver, SavedAsset},
+    transformer::IdentityAssetTransformer,
+};
+
+/// Saves an [InkText] as its compiled JSON.
+#[derive(Debug, Default)]
+pub struct InkJsonSaver;
+
+impl AssetSaver for InkJsonSaver {
+    type Asset = InkText;
+    type Settings = ();
+    type OutputLoader = InkTextLoader;
+    type Error = std::io::Error;
+
+    async fn save(
+        &self,
+        writer: &mut Writer,
+        asset: SavedAsset<'_, Self::Asset>,
+        _settings: &Self::Settings,
+    ) -> Result<LoaderSettings, Self::Error> {
+        writer.write_all(asset.0.as_bytes()).await?;
+        Ok(LoaderSettings {
+            format: Some(InkFormat::Json),
+            ..default()
+        })
+    }
+}
+
+/// Compiles ink source with [InkTextLoader] and saves the JSON with
+/// [InkJsonSaver]. [InkPlugin] makes it the default processor for `.ink`.
+pub type InkCompileProcessor =
+    LoadTransformAndSave<InkTextLoader, IdentityAssetTransformer<InkText>, InkJsonSaver>;
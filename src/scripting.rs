@@ -1,18 +1,28 @@
 use super::*;
+use bevy::ecs::system::SystemParam;
+use bevy::reflect::TypeRegistry;
 use bevy_mod_scripting::{
     GetTypeDependencies,
     bindings::{
         AppReflectAllocator, InteropError, IntoScriptRef, ReflectReference, WorldAccessGuard,
         function::from::Val, script_value::ScriptValue,
     },
+    core::{
+        event::{CallbackLabel, Recipients},
+        script::ScriptComponent,
+    },
     lua::{LuaScriptingPlugin, mlua::UserData},
     prelude::{ScriptCallbackEvent, callback_labels, event_handler},
 };
-use bladeink::choice::Choice;
 
 pub(crate) fn plugin(app: &mut App) {
     app.register_type::<InkStoryRef>()
-        .add_systems(Update, on_reload_eval_func.after(hot_reload_on_modify));
+        .add_systems(
+            Update,
+            on_reload_eval_func
+                .after(hot_reload_on_modify)
+                .in_set(InkSystems),
+        );
     lua::plugin(app);
 }
 
@@ -32,35 +42,147 @@ impl InkStoryRef {
 
 impl UserData for InkStoryRef {}
 
+/// Add to a story entity to send its script callbacks, e.g.,
+/// `on_story_reload`, only to these recipients instead of to every script, so
+/// scripts that don't drive this story aren't called for it.
+#[derive(Debug, Component, Clone)]
+pub struct InkScriptRecipients(pub Vec<Recipients>);
+
+/// Add to a story entity with a [ScriptComponent] to send its script
+/// callbacks, e.g., `on_continue`, only to the scripts attached to it, such as
+/// an NPC's own controller script. [InkScriptRecipients] comes first if both
+/// are present.
+#[derive(Debug, Component, Clone)]
+pub struct InkEntityScripts;
+
+/// Marks a story entity made by a script's `fork`, the only kind its
+/// `discard` despawns.
+#[derive(Debug, Component, Clone, Copy)]
+pub struct InkScriptFork;
+
+/// Which scripts a story's callbacks go to, e.g., for a debug overlay that
+/// shows why a script's `on_story_reload` isn't called.
+#[derive(SystemParam)]
+pub struct InkScriptTargets<'w, 's> {
+    recipients: Query<'w, 's, &'static InkScriptRecipients>,
+    entity_scripts: Query<'w, 's, &'static ScriptComponent, With<InkEntityScripts>>,
+}
+
+impl InkScriptTargets<'_, '_> {
+    /// Returns the recipients of the story's callbacks as its
+    /// [InkScriptRecipients] or [InkEntityScripts] say, or `None` if they go
+    /// to every script.
+    pub fn recipients(&self, entity: Entity) -> Option<Vec<Recipients>> {
+        if let Ok(InkScriptRecipients(recipients)) = self.recipients.get(entity) {
+            Some(recipients.clone())
+        } else if let Ok(scripts) = self.entity_scripts.get(entity) {
+            Some(
+                scripts
+                    .iter()
+                    .map(|script| Recipients::ScriptEntity(script.id(), entity))
+                    .collect(),
+            )
+        } else {
+            None
+        }
+    }
+}
+
 fn on_reload_eval_func(
     mut events: EventReader<InkEvent>,
     mut writer: EventWriter<ScriptCallbackEvent>,
     allocator: ResMut<AppReflectAllocator>,
+    targets: InkScriptTargets,
 ) {
+    let mut send = |entity: Entity, label: CallbackLabel, args: Vec<ScriptValue>| {
+        let Some(targets) = targets.recipients(entity) else {
+            writer.write(ScriptCallbackEvent::new_for_all_scripts(label, args));
+            return;
+        };
+        for recipient in targets {
+            writer.write(ScriptCallbackEvent::new(
+                label.clone(),
+                args.clone(),
+                recipient,
+                None,
+            ));
+        }
+    };
     // For each modified asset, rebuild the runtime for all referencing entities.
     for ev in events.read() {
         match ev {
-            InkEvent::OnStoryReload(id) => {
-                let story_ref = InkStoryRef(*id);
+            InkEvent::OnStoryReload {
+                entity,
+                preserved,
+                tags_changed,
+            } => {
+                let story_ref = InkStoryRef(*entity);
                 let mut allocator = allocator.write();
                 let story_ref = ReflectReference::new_allocated(story_ref, &mut allocator);
 
-                writer.write(ScriptCallbackEvent::new_for_all_scripts(
-                    OnStoryReload,
-                    vec![story_ref.into()],
-                ));
+                send(
+                    *entity,
+                    OnStoryReload.into(),
+                    vec![
+                        story_ref.into(),
+                        ScriptValue::Bool(*preserved),
+                        ScriptValue::Bool(*tags_changed),
+                    ],
+                );
             }
+            InkEvent::OnContinue { entity, text, tags } => {
+                let story_ref = InkStoryRef(*entity);
+                let mut allocator = allocator.write();
+                let story_ref = ReflectReference::new_allocated(story_ref, &mut allocator);
+
+                send(
+                    *entity,
+                    OnContinue.into(),
+                    vec![
+                        story_ref.into(),
+                        ScriptValue::String(text.clone().into()),
+                        ScriptValue::List(
+                            tags.iter()
+                                .map(|tag| ScriptValue::String(tag.clone().into()))
+                                .collect(),
+                        ),
+                    ],
+                );
+            }
+            InkEvent::VariableChanged {
+                entity,
+                name,
+                value,
+            } => {
+                let story_ref = InkStoryRef(*entity);
+                let mut allocator = allocator.write();
+                let story_ref = ReflectReference::new_allocated(story_ref, &mut allocator);
+
+                send(
+                    *entity,
+                    OnVariableChanged.into(),
+                    vec![
+                        story_ref.into(),
+                        ScriptValue::String(name.clone().into()),
+                        lua::to_script_value(value),
+                    ],
+                );
+            }
+            _ => {}
         }
     }
 }
 
-callback_labels!(OnStoryReload => "on_story_reload");
+callback_labels!(
+    OnStoryReload => "on_story_reload",
+    OnContinue => "on_continue",
+    OnVariableChanged => "on_variable_changed"
+);
 
 mod lua {
     use super::*;
 
     use bevy_mod_scripting::bindings::{
-        ReflectAccessId,
         function::{
             namespace::{GlobalNamespace, NamespaceBuilder},
             script_function::FunctionCallContext,
@@ -69,53 +191,48 @@ mod lua {
     pub(crate) fn plugin(app: &mut App) {
         app.add_systems(
             PostUpdate,
-            event_handler::<OnStoryReload, LuaScriptingPlugin>,
+            (
+                event_handler::<OnStoryReload, LuaScriptingPlugin>,
+                event_handler::<OnContinue, LuaScriptingPlugin>,
+                event_handler::<OnVariableChanged, LuaScriptingPlugin>,
+            )
+                .in_set(InkSystems),
         );
         let world = app.world_mut();
 
-        NamespaceBuilder::<GlobalNamespace>::new_unregistered(world).register(
+        NamespaceBuilder::<GlobalNamespace>::new_unregistered(world).register_documented(
             "ink_load",
             |ctx: FunctionCallContext, path: String| -> Result<ScriptValue, InteropError> {
                 let world_guard = ctx.world()?;
-                let raid = ReflectAccessId::for_global();
-                if world_guard.claim_global_access() {
-                    let ink_story_ref = {
-                        let world = world_guard.as_unsafe_world_cell()?;
-                        let world = unsafe { world.world_mut() };
-                        let ink_text = {
-                            let asset_server = world.resource::<AssetServer>();
-                            asset_server.load::<InkText>(&path)
-                        };
-                        let id = world.spawn(InkLoad(ink_text)).id();
-                        InkStoryRef(id)
-                    };
-                    unsafe { world_guard.release_global_access() };
-                    ink_story_ref.into_script_ref(world_guard)
-                } else {
-                    Err(InteropError::cannot_claim_access(
-                        raid,
-                        world_guard.get_access_location(raid),
-                        "ink_load",
-                    ))
-                }
+                // Scoped access is released on every path, unlike a manual
+                // claim that an early `?` could leak.
+                let ink_story_ref = world_guard.with_global_access(|world| {
+                    let ink_text = world.resource::<AssetServer>().load::<InkText>(&path);
+                    InkStoryRef(world.spawn(InkLoad(ink_text)).id())
+                })?;
+                ink_story_ref.into_script_ref(world_guard)
             },
+            "Loads the ink at `path` into a new story entity and returns it.",
+            &["ctx", "path"],
         );
 
         NamespaceBuilder::<InkStoryRef>::new(app.world_mut())
-            .register(
+            .register_documented(
                 "can_continue",
                 |ctx: FunctionCallContext, this: Val<InkStoryRef>| -> Result<bool, InteropError> {
                     let world = ctx.world()?;
                     world.with_global_access(|world| {
                         let stories = world.non_send_resource::<InkStories>();
                         stories
-                            .get(this.0.0)
-                            .map(|story| story.can_continue())
+                            .can_continue(this.0.0)
                             .map_err(|e| InteropError::external(Box::new(e)))
                     })?
                 },
+                "Whether the story has more text before its next choice and doesn't wait on an \
+                 async external.",
+                &["ctx", "self"],
             )
-            .register(
+            .register_documented(
                 "is_loaded",
                 |ctx: FunctionCallContext, this: Val<InkStoryRef>| -> Result<bool, InteropError> {
                     let world = ctx.world()?;
@@ -124,8 +241,60 @@ mod lua {
                         stories.get(this.0.0).is_ok()
                     })
                 },
+                "Whether the story's ink has loaded and parsed.",
+                &["ctx", "self"],
+            )
+            .register_documented(
+                "unload",
+                |ctx: FunctionCallContext, this: Val<InkStoryRef>| -> Result<bool, InteropError> {
+                    let world = ctx.world()?;
+                    world.with_global_access(|world| unload_ink(world, this.0.0))
+                },
+                "Stops the story and frees it, keeping its entity.",
+                &["ctx", "self"],
+            )
+            .register_documented(
+                "fork",
+                |ctx: FunctionCallContext,
+                 this: Val<InkStoryRef>|
+                 -> Result<ScriptValue, InteropError> {
+                    let world_guard = ctx.world()?;
+                    let fork = world_guard.with_global_access(|world| {
+                        let fork = world.spawn((InkStory, InkScriptFork)).id();
+                        let result = world
+                            .non_send_resource_mut::<InkStories>()
+                            .fork_to(this.0.0, fork);
+                        if result.is_err() {
+                            world.despawn(fork);
+                        }
+                        result.map(|()| InkStoryRef(fork))
+                    })?;
+                    fork.map_err(|e| InteropError::external(Box::new(e)))?
+                        .into_script_ref(world_guard)
+                },
+                "Returns an independent copy of the story on a new entity, e.g., to look ahead \
+                 at where a choice leads. The copy takes memory until it's `discard`ed.",
+                &["ctx", "self"],
+            )
+            .register_documented(
+                "discard",
+                |ctx: FunctionCallContext, this: Val<InkStoryRef>| -> Result<bool, InteropError> {
+                    let world = ctx.world()?;
+                    world.with_global_access(|world| {
+                        if world.get::<InkScriptFork>(this.0.0).is_none() {
+                            return Err(InteropError::str(
+                                "only a story made by `fork` can be discarded",
+                            ));
+                        }
+                        let unloaded = unload_ink(world, this.0.0);
+                        world.despawn(this.0.0);
+                        Ok(unloaded)
+                    })?
+                },
+                "Frees a story made by `fork` and despawns its entity. Errors for any other story.",
+                &["ctx", "self"],
             )
-            .register(
+            .register_documented(
                 "get_current_tags",
                 |ctx: FunctionCallContext,
                  this: Val<InkStoryRef>|
@@ -139,50 +308,378 @@ mod lua {
                                 story
                                     .get_current_tags()
                                     .map(to_tags)
-                                    .map_err(|e| InkError::from(e))
+                                    .map_err(InkError::from)
                             )
                             .map_err(|e| InteropError::external(Box::new(e)))
                     })?
                 },
+                "Returns the tags of the current line.",
+                &["ctx", "self"],
+            )
+            .register_documented(
+                "global_tags",
+                |ctx: FunctionCallContext,
+                 this: Val<InkStoryRef>|
+                 -> Result<ScriptValue, InteropError> {
+                    let world = ctx.world()?;
+                    world.with_global_access(|world| {
+                        let stories = world.non_send_resource::<InkStories>();
+                        stories
+                            .global_tags(this.0.0)
+                            .map(to_tags)
+                            .map_err(|e| InteropError::external(Box::new(e)))
+                    })?
+                },
+                "Returns the tags at the very top of the story.",
+                &["ctx", "self"],
+            )
+            .register_documented(
+                "current_tags",
+                |ctx: FunctionCallContext,
+                 this: Val<InkStoryRef>|
+                 -> Result<ScriptValue, InteropError> {
+                    let world = ctx.world()?;
+                    world.with_global_access(|world| {
+                        let mut stories = world.non_send_resource_mut::<InkStories>();
+                        stories
+                            .current_tags(this.0.0)
+                            .map(to_tags)
+                            .map_err(|e| InteropError::external(Box::new(e)))
+                    })?
+                },
+                "Returns the tags of the latest `cont`'s line as a list, empty if it has none.",
+                &["ctx", "self"],
+            )
+            .register_documented(
+                "current_tags_map",
+                |ctx: FunctionCallContext,
+                 this: Val<InkStoryRef>|
+                 -> Result<ScriptValue, InteropError> {
+                    let world = ctx.world()?;
+                    world.with_global_access(|world| {
+                        let mut stories = world.non_send_resource_mut::<InkStories>();
+                        stories
+                            .get_mut(this.0.0)
+                            .and_then(|story| story.get_current_tags().map_err(InkError::from))
+                            .map(|tags| {
+                                ScriptValue::Map(
+                                    parse_tags(&tags)
+                                        .into_iter()
+                                        .map(|(key, value)| (key, ScriptValue::String(value.into())))
+                                        .collect(),
+                                )
+                            })
+                            .map_err(|e| InteropError::external(Box::new(e)))
+                    })?
+                },
+                "Returns the current line's `key: value` tags as a table.",
+                &["ctx", "self"],
+            )
+            .register_documented(
+                "has_var",
+                |ctx: FunctionCallContext,
+                 this: Val<InkStoryRef>,
+                 name: String|
+                 -> Result<bool, InteropError> {
+                    let world = ctx.world()?;
+                    world.with_global_access(|world| {
+                        let stories = world.non_send_resource::<InkStories>();
+                        stories
+                            .has_variable(this.0.0, &name)
+                            .map_err(|e| InteropError::external(Box::new(e)))
+                    })?
+                },
+                "Whether the story declares the global variable `name`.",
+                &["ctx", "self", "name"],
+            )
+            .register_documented(
+                "var_json",
+                |ctx: FunctionCallContext,
+                 this: Val<InkStoryRef>,
+                 name: String|
+                 -> Result<String, InteropError> {
+                    let world = ctx.world()?;
+                    world.with_global_access(|world| {
+                        let stories = world.non_send_resource::<InkStories>();
+                        stories
+                            .variable_as_json(this.0.0, &name)
+                            .map_err(|e| InteropError::external(Box::new(e)))
+                    })?
+                },
+                "Returns the global variable `name` as a JSON string; lists are arrays of \
+                 item names.",
+                &["ctx", "self", "name"],
+            )
+            .register_documented(
+                "set_var",
+                |ctx: FunctionCallContext,
+                 this: Val<InkStoryRef>,
+                 name: String,
+                 value: ScriptValue|
+                 -> Result<ScriptValue, InteropError> {
+                    let value = from_script_value(value).ok_or_else(|| {
+                        InteropError::external(Box::new(InkError::CannotSetVariable(name.clone())))
+                    })?;
+                    let world = ctx.world()?;
+                    world.with_global_access(|world| {
+                        let mut stories = world.non_send_resource_mut::<InkStories>();
+                        stories
+                            .set_variable(this.0.0, &name, &value)
+                            .map(|value| to_script_value(&value))
+                            .map_err(|e| InteropError::external(Box::new(e)))
+                    })?
+                },
+                "Sets the global variable `name` and returns the value stored, e.g., \
+                 `2.0` for `2` set to a float variable.",
+                &["ctx", "self", "name", "value"],
+            )
+            .register_documented(
+                "set_vars",
+                |ctx: FunctionCallContext,
+                 this: Val<InkStoryRef>,
+                 variables: HashMap<String, ScriptValue>|
+                 -> Result<ScriptValue, InteropError> {
+                    let variables = variables
+                        .into_iter()
+                        .map(|(name, value)| match from_script_value(value) {
+                            Some(value) => Ok((name, value)),
+                            None => Err(InteropError::external(Box::new(
+                                InkError::CannotSetVariable(name),
+                            ))),
+                        })
+                        .collect::<Result<HashMap<_, _>, _>>()?;
+                    let world = ctx.world()?;
+                    world.with_global_access(|world| {
+                        let mut stories = world.non_send_resource_mut::<InkStories>();
+                        stories
+                            .set_variables(this.0.0, &variables)
+                            .map(|stored| {
+                                ScriptValue::Map(
+                                    stored
+                                        .iter()
+                                        .map(|(name, value)| (name.clone(), to_script_value(value)))
+                                        .collect(),
+                                )
+                            })
+                            .map_err(|e| InteropError::external(Box::new(e)))
+                    })?
+                },
+                "Sets every variable in the table at once, or none if one fails, and returns \
+                 the values stored.",
+                &["ctx", "self", "variables"],
+            )
+            .register_documented(
+                "has_external",
+                |ctx: FunctionCallContext,
+                 this: Val<InkStoryRef>,
+                 name: String|
+                 -> Result<bool, InteropError> {
+                    let world = ctx.world()?;
+                    world.with_global_access(|world| {
+                        let stories = world.non_send_resource::<InkStories>();
+                        stories
+                            .has_external(this.0.0, &name)
+                            .map_err(|e| InteropError::external(Box::new(e)))
+                    })?
+                },
+                "Whether the `EXTERNAL` function `name` is bound.",
+                &["ctx", "self", "name"],
+            )
+            .register_documented(
+                "observe",
+                |ctx: FunctionCallContext,
+                 this: Val<InkStoryRef>,
+                 name: String|
+                 -> Result<(), InteropError> {
+                    let world = ctx.world()?;
+                    world.with_global_access(|world| {
+                        let mut stories = world.non_send_resource_mut::<InkStories>();
+                        stories
+                            .observe_variable(this.0.0, &name)
+                            .map_err(|e| InteropError::external(Box::new(e)))
+                    })?
+                },
+                "Calls `on_variable_changed` whenever the variable `name` changes.",
+                &["ctx", "self", "name"],
+            )
+            .register_documented(
+                "variables",
+                |ctx: FunctionCallContext,
+                 this: Val<InkStoryRef>|
+                 -> Result<ScriptValue, InteropError> {
+                    let world = ctx.world()?;
+                    world.with_global_access(|world| {
+                        let stories = world.non_send_resource::<InkStories>();
+                        stories
+                            .all_variables(this.0.0)
+                            .map(|variables| {
+                                ScriptValue::Map(
+                                    variables
+                                        .iter()
+                                        .map(|(name, value)| (name.clone(), to_script_value(value)))
+                                        .collect(),
+                                )
+                            })
+                            .map_err(|e| InteropError::external(Box::new(e)))
+                    })?
+                },
+                "Returns every global variable by name with its value.",
+                &["ctx", "self"],
+            )
+            .register_documented(
+                "list_add",
+                |ctx: FunctionCallContext,
+                 this: Val<InkStoryRef>,
+                 variable: String,
+                 item: String|
+                 -> Result<(), InteropError> {
+                    let world = ctx.world()?;
+                    world.with_global_access(|world| {
+                        let mut stories = world.non_send_resource_mut::<InkStories>();
+                        stories
+                            .list_add(this.0.0, &variable, &item)
+                            .map_err(|e| InteropError::external(Box::new(e)))
+                    })?
+                },
+                "Adds `item` to the list variable `variable`.",
+                &["ctx", "self", "variable", "item"],
             )
-            .register(
+            .register_documented(
+                "list_remove",
+                |ctx: FunctionCallContext,
+                 this: Val<InkStoryRef>,
+                 variable: String,
+                 item: String|
+                 -> Result<(), InteropError> {
+                    let world = ctx.world()?;
+                    world.with_global_access(|world| {
+                        let mut stories = world.non_send_resource_mut::<InkStories>();
+                        stories
+                            .list_remove(this.0.0, &variable, &item)
+                            .map_err(|e| InteropError::external(Box::new(e)))
+                    })?
+                },
+                "Removes `item` from the list variable `variable`.",
+                &["ctx", "self", "variable", "item"],
+            )
+            .register_documented(
+                "list_contains",
+                |ctx: FunctionCallContext,
+                 this: Val<InkStoryRef>,
+                 variable: String,
+                 item: String|
+                 -> Result<bool, InteropError> {
+                    let world = ctx.world()?;
+                    world.with_global_access(|world| {
+                        let stories = world.non_send_resource::<InkStories>();
+                        stories
+                            .list_contains(this.0.0, &variable, &item)
+                            .map_err(|e| InteropError::external(Box::new(e)))
+                    })?
+                },
+                "Whether the list variable `variable` holds `item`.",
+                &["ctx", "self", "variable", "item"],
+            )
+            .register_documented(
                 "get_current_choices",
                 |ctx: FunctionCallContext,
                  this: Val<InkStoryRef>|
                  -> Result<ScriptValue, InteropError> {
-                    fn choice_to_script_value(choice: &Choice) -> ScriptValue {
-                        let mut map: HashMap<String, ScriptValue> = HashMap::new();
-                        map.insert(
-                            "text".to_string(),
-                            ScriptValue::String(choice.text.clone().into()),
-                        );
-                        let tags: Vec<ScriptValue> = choice.tags
-                            .iter()
-                            .inspect(|tag| info!("tag '{tag}'"))
-                            .map(|tag| ScriptValue::String(tag.clone().into()))
-                            .collect();
-                        map.insert(
-                            "tags".to_string(),
-                            ScriptValue::List(tags));
-                        ScriptValue::Map(map)
-                    }
-                    let world = ctx.world()?;
-                    world.with_global_access(|world| {
-                        let stories = world.non_send_resource::<InkStories>();
-                        stories
-                            .get(this.0.0)
-                            .map(|story| {
-                                ScriptValue::List(story
-                                    .get_current_choices()
-                                    .iter()
-                                                  .map(|choice| choice_to_script_value(choice))
-                                    .collect())
+                    let world = ctx.world()?;
+                    world.with_global_access(|world| {
+                        let stories = world.non_send_resource::<InkStories>();
+                        stories
+                            .current_choices(this.0.0)
+                            .map(|choices| {
+                                ScriptValue::List(
+                                    choices.iter().map(choice_info_to_script_value).collect(),
+                                )
+                            })
+                            .map_err(|e| InteropError::external(Box::new(e)))
+                    })?
+                },
+                "Returns the choices on offer now, after any choice filter, as tables of \
+                 their `index`, `text`, and `tags`.",
+                &["ctx", "self"],
+            )
+            .register_documented(
+                "choices_detailed",
+                |ctx: FunctionCallContext,
+                 this: Val<InkStoryRef>|
+                 -> Result<ScriptValue, InteropError> {
+                    let world = ctx.world()?;
+                    world.with_global_access(|world| {
+                        let stories = world.non_send_resource::<InkStories>();
+                        stories
+                            .current_choices(this.0.0)
+                            .map(|choices| {
+                                ScriptValue::List(
+                                    choices.iter().map(choice_info_to_script_value).collect(),
+                                )
+                            })
+                            .map_err(|e| InteropError::external(Box::new(e)))
+                    })?
+                },
+                "Returns the choices on offer now as tables of their `index` to choose them \
+                 by, `text`, and `tags`, empty if they have none.",
+                &["ctx", "self"],
+            )
+            .register_documented(
+                "choice_count",
+                |ctx: FunctionCallContext,
+                 this: Val<InkStoryRef>|
+                 -> Result<usize, InteropError> {
+                    let world = ctx.world()?;
+                    world.with_global_access(|world| {
+                        let stories = world.non_send_resource::<InkStories>();
+                        stories
+                            .choice_count(this.0.0)
+                            .map_err(|e| InteropError::external(Box::new(e)))
+                    })?
+                },
+                "Returns how many choices are on offer now.",
+                &["ctx", "self"],
+            )
+            .register_documented(
+                "choice_text",
+                |ctx: FunctionCallContext,
+                 this: Val<InkStoryRef>,
+                 index: usize|
+                 -> Result<String, InteropError> {
+                    let world = ctx.world()?;
+                    world.with_global_access(|world| {
+                        let stories = world.non_send_resource::<InkStories>();
+                        stories
+                            .choice_text(this.0.0, index)
+                            .map_err(|e| InteropError::external(Box::new(e)))
+                    })?
+                },
+                "Returns the text of the current choice at `index`.",
+                &["ctx", "self", "index"],
+            )
+            .register_documented(
+                "choices_with_tag",
+                |ctx: FunctionCallContext,
+                 this: Val<InkStoryRef>,
+                 tag: String|
+                 -> Result<ScriptValue, InteropError> {
+                    let world = ctx.world()?;
+                    world.with_global_access(|world| {
+                        let stories = world.non_send_resource::<InkStories>();
+                        stories
+                            .choices_with_tag(this.0.0, &tag)
+                            .map(|choices| {
+                                ScriptValue::List(
+                                    choices.iter().map(choice_info_to_script_value).collect(),
+                                )
                             })
                             .map_err(|e| InteropError::external(Box::new(e)))
                     })?
                 },
+                "Returns the current choices tagged `tag`.",
+                &["ctx", "self", "tag"],
             )
-            .register(
+            .register_documented(
                 "choose_choice_index",
                 |ctx: FunctionCallContext,
                  this: Val<InkStoryRef>,
@@ -192,15 +689,230 @@ mod lua {
                     world.with_global_access(|world| {
                         let mut stories = world.non_send_resource_mut::<InkStories>();
                         stories
-                            .get_mut(this.0.0)
-                            .and_then(|story| {
-                                story.choose_choice_index(index).map_err(InkError::from)
+                            .choose_choice_index(this.0.0, index)
+                            .map_err(|e| InteropError::external(Box::new(e)))
+                    })?
+                },
+                "Chooses the current choice at `index`.",
+                &["ctx", "self", "index"],
+            )
+            .register_documented(
+                "choose_and_continue",
+                |ctx: FunctionCallContext,
+                 this: Val<InkStoryRef>,
+                 index: usize|
+                 -> Result<String, InteropError> {
+                    let world = ctx.world()?;
+                    world.with_global_access(|world| {
+                        let mut stories = world.non_send_resource_mut::<InkStories>();
+                        stories
+                            .choose_and_continue(this.0.0, index)
+                            .map_err(|e| InteropError::external(Box::new(e)))
+                    })?
+                },
+                "Chooses the current choice at `index` and returns the next line.",
+                &["ctx", "self", "index"],
+            )
+            .register_documented(
+                "choose_choice_by_text",
+                |ctx: FunctionCallContext,
+                 this: Val<InkStoryRef>,
+                 text: String|
+                 -> Result<(), InteropError> {
+                    let world = ctx.world()?;
+                    world.with_global_access(|world| {
+                        let mut stories = world.non_send_resource_mut::<InkStories>();
+                        stories
+                            .choose_choice_by_text(this.0.0, &text)
+                            .map_err(|e| InteropError::external(Box::new(e)))
+                    })?
+                },
+                "Chooses the current choice whose text is `text`.",
+                &["ctx", "self", "text"],
+            )
+            .register_documented(
+                "current_text",
+                |ctx: FunctionCallContext,
+                 this: Val<InkStoryRef>|
+                 -> Result<String, InteropError> {
+                    let world = ctx.world()?;
+                    world.with_global_access(|world| {
+                        let stories = world.non_send_resource::<InkStories>();
+                        stories
+                            .current_text(this.0.0)
+                            .map_err(|e| InteropError::external(Box::new(e)))
+                    })?
+                },
+                "Returns the line produced by the latest `cont`.",
+                &["ctx", "self"],
+            )
+            .register_documented(
+                "current_turn_index",
+                |ctx: FunctionCallContext,
+                 this: Val<InkStoryRef>|
+                 -> Result<i32, InteropError> {
+                    let world = ctx.world()?;
+                    world.with_global_access(|world| {
+                        let stories = world.non_send_resource::<InkStories>();
+                        stories
+                            .current_turn_index(this.0.0)
+                            .map_err(|e| InteropError::external(Box::new(e)))
+                    })?
+                },
+                "Returns ink's turn index, one less than `TURNS()`.",
+                &["ctx", "self"],
+            )
+            .register_documented(
+                "set_seed",
+                |ctx: FunctionCallContext,
+                 this: Val<InkStoryRef>,
+                 seed: u64|
+                 -> Result<(), InteropError> {
+                    let world = ctx.world()?;
+                    world.with_global_access(|world| {
+                        let mut stories = world.non_send_resource_mut::<InkStories>();
+                        stories
+                            .set_seed(this.0.0, seed)
+                            .map_err(|e| InteropError::external(Box::new(e)))
+                    })?
+                },
+                "Seeds `RANDOM()` and shuffles; best done before the first `cont`.",
+                &["ctx", "self", "seed"],
+            )
+            .register_documented(
+                "current_path",
+                |ctx: FunctionCallContext,
+                 this: Val<InkStoryRef>|
+                 -> Result<Option<String>, InteropError> {
+                    let world = ctx.world()?;
+                    world.with_global_access(|world| {
+                        let stories = world.non_send_resource::<InkStories>();
+                        stories
+                            .current_path(this.0.0)
+                            .map_err(|e| InteropError::external(Box::new(e)))
+                    })?
+                },
+                "Returns the knot or stitch the story is in; nil before it starts, once it \
+                 ends, or outside any knot.",
+                &["ctx", "self"],
+            )
+            .register_documented(
+                "is_first_visit",
+                |ctx: FunctionCallContext, this: Val<InkStoryRef>| -> Result<bool, InteropError> {
+                    let world = ctx.world()?;
+                    world.with_global_access(|world| {
+                        let stories = world.non_send_resource::<InkStories>();
+                        stories
+                            .is_first_visit(this.0.0)
+                            .map_err(|e| InteropError::external(Box::new(e)))
+                    })?
+                },
+                "Returns whether the story is in its current knot for the first time.",
+                &["ctx", "self"],
+            )
+            .register_documented(
+                "knot_names",
+                |ctx: FunctionCallContext,
+                 this: Val<InkStoryRef>|
+                 -> Result<ScriptValue, InteropError> {
+                    let world = ctx.world()?;
+                    world.with_global_access(|world| {
+                        let stories = world.non_send_resource::<InkStories>();
+                        stories
+                            .knot_names(this.0.0)
+                            .map(|names| {
+                                ScriptValue::List(
+                                    names
+                                        .into_iter()
+                                        .map(|name| ScriptValue::String(name.into()))
+                                        .collect(),
+                                )
+                            })
+                            .map_err(|e| InteropError::external(Box::new(e)))
+                    })?
+                },
+                "Returns the names of the story's top-level knots.",
+                &["ctx", "self"],
+            )
+            .register_documented(
+                "step",
+                |ctx: FunctionCallContext,
+                 this: Val<InkStoryRef>|
+                 -> Result<ScriptValue, InteropError> {
+                    let world = ctx.world()?;
+                    world.with_global_access(|world| {
+                        let mut stories = world.non_send_resource_mut::<InkStories>();
+                        stories
+                            .step(this.0.0)
+                            .map(|step| {
+                                let mut map: HashMap<String, ScriptValue> = HashMap::new();
+                                let choices = step.choices.iter();
+                                map.insert(
+                                    "text".to_string(),
+                                    ScriptValue::String(step.text.into()),
+                                );
+                                map.insert("tags".to_string(), to_tags(step.tags));
+                                map.insert(
+                                    "choices".to_string(),
+                                    ScriptValue::List(
+                                        choices.map(choice_info_to_script_value).collect(),
+                                    ),
+                                );
+                                ScriptValue::Map(map)
                             })
                             .map_err(|e| InteropError::external(Box::new(e)))
                     })?
                 },
+                "Continues one line and returns its text, tags, and choices.",
+                &["ctx", "self"],
             )
-            .register(
+            .register_documented(
+                "state",
+                |ctx: FunctionCallContext,
+                 this: Val<InkStoryRef>|
+                 -> Result<ScriptValue, InteropError> {
+                    let world = ctx.world()?;
+                    world.with_global_access(|world| {
+                        let stories = world.non_send_resource::<InkStories>();
+                        stories
+                            .state(this.0.0)
+                            .map(|state| {
+                                let mut map: HashMap<String, ScriptValue> = HashMap::new();
+                                let kind = match state {
+                                    InkStoryState::Continuable => "continuable",
+                                    InkStoryState::AtChoice(choices) => {
+                                        map.insert(
+                                            "choices".to_string(),
+                                            ScriptValue::List(
+                                                choices
+                                                    .iter()
+                                                    .map(choice_info_to_script_value)
+                                                    .collect(),
+                                            ),
+                                        );
+                                        "choice"
+                                    }
+                                    InkStoryState::Ended => "ended",
+                                    InkStoryState::AwaitingExternal(pending) => {
+                                        map.insert(
+                                            "name".to_string(),
+                                            ScriptValue::String(pending.name.into()),
+                                        );
+                                        "external"
+                                    }
+                                };
+                                map.insert("kind".to_string(), ScriptValue::String(kind.into()));
+                                ScriptValue::Map(map)
+                            })
+                            .map_err(|e| InteropError::external(Box::new(e)))
+                    })?
+                },
+                "Returns where the story is as a table whose `kind` is `\"continuable\"`, \
+                 `\"choice\"` with its `choices`, `\"ended\"`, or `\"external\"` with the \
+                 `name` of the async external it waits on.",
+                &["ctx", "self"],
+            )
+            .register_documented(
                 "cont",
                 |ctx: FunctionCallContext,
                  this: Val<InkStoryRef>|
@@ -209,14 +921,154 @@ mod lua {
                     world.with_global_access(|world| {
                         let mut stories = world.non_send_resource_mut::<InkStories>();
                         stories
-                            .get_mut(this.0.0)
-                            .and_then(|story| story.cont().map_err(InkError::from))
+                            .cont(this.0.0)
+                            .map_err(|e| InteropError::external(Box::new(e)))
+                    })?
+                },
+                "Continues the story one line and returns it.",
+                &["ctx", "self"],
+            )
+            .register_documented(
+                "cont_to_choice",
+                |ctx: FunctionCallContext,
+                 this: Val<InkStoryRef>|
+                 -> Result<String, InteropError> {
+                    let world = ctx.world()?;
+                    world.with_global_access(|world| {
+                        let mut stories = world.non_send_resource_mut::<InkStories>();
+                        stories
+                            .cont_maximally(this.0.0)
+                            .map(|lines| lines.concat())
+                            .map_err(|e| InteropError::external(Box::new(e)))
+                    })?
+                },
+                "Continues to the next choices or the end and returns the text joined. Errors \
+                 after `max_continues` lines in case the story loops forever.",
+                &["ctx", "self"],
+            )
+            .register_documented(
+                "cont_n",
+                |ctx: FunctionCallContext,
+                 this: Val<InkStoryRef>,
+                 n: usize|
+                 -> Result<ScriptValue, InteropError> {
+                    let world = ctx.world()?;
+                    world.with_global_access(|world| {
+                        let mut stories = world.non_send_resource_mut::<InkStories>();
+                        stories
+                            .cont_n(this.0.0, n)
+                            .map(|lines| {
+                                ScriptValue::List(
+                                    lines
+                                        .into_iter()
+                                        .map(|line| ScriptValue::String(line.into()))
+                                        .collect(),
+                                )
+                            })
+                            .map_err(|e| InteropError::external(Box::new(e)))
+                    })?
+                },
+                "Continues up to `n` lines, stopping early at choices or the end.",
+                &["ctx", "self", "n"],
+            )
+            .register_documented(
+                "save_state",
+                |ctx: FunctionCallContext,
+                 this: Val<InkStoryRef>|
+                 -> Result<String, InteropError> {
+                    let world = ctx.world()?;
+                    world.with_global_access(|world| {
+                        let stories = world.non_send_resource::<InkStories>();
+                        stories
+                            .save_state(this.0.0)
                             .map_err(|e| InteropError::external(Box::new(e)))
                     })?
                 },
+                "Returns the story's state as JSON, for `load_state`.",
+                &["ctx", "self"],
+            )
+            .register_documented(
+                "load_state",
+                |ctx: FunctionCallContext,
+                 this: Val<InkStoryRef>,
+                 json: String|
+                 -> Result<(), InteropError> {
+                    let world = ctx.world()?;
+                    world.with_global_access(|world| {
+                        let mut stories = world.non_send_resource_mut::<InkStories>();
+                        stories
+                            .load_state(this.0.0, &json)
+                            .map_err(|e| InteropError::external(Box::new(e)))
+                    })?
+                },
+                "Loads a state from `save_state`; errors and starts over if it doesn't fit.",
+                &["ctx", "self", "json"],
+            )
+            // Not `goto`, which is a keyword in Lua.
+            .register_documented(
+                "goto_and_continue",
+                |ctx: FunctionCallContext,
+                 this: Val<InkStoryRef>,
+                 path: String|
+                 -> Result<String, InteropError> {
+                    let world = ctx.world()?;
+                    world.with_global_access(|world| {
+                        let mut stories = world.non_send_resource_mut::<InkStories>();
+                        stories
+                            .goto_and_continue(this.0.0, &path)
+                            .map_err(|e| InteropError::external(Box::new(e)))
+                    })?
+                },
+                "Jumps to `path`, e.g., \"knot.stitch\", and returns the first line there.",
+                &["ctx", "self", "path"],
             );
     }
 
+    pub(super) fn to_script_value(value: &VariableValue) -> ScriptValue {
+        match value {
+            VariableValue::Bool(b) => ScriptValue::Bool(*b),
+            VariableValue::Int(i) => ScriptValue::Integer(*i as i64),
+            VariableValue::Float(f) => ScriptValue::Float(*f as f64),
+            VariableValue::String(s) | VariableValue::DivertTarget(s) => {
+                ScriptValue::String(s.clone().into())
+            }
+            VariableValue::List(items) => ScriptValue::List(
+                items
+                    .iter()
+                    .map(|item| ScriptValue::String(item.clone().into()))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Returns `None` for values no ink variable holds, e.g., a function.
+    fn from_script_value(value: ScriptValue) -> Option<VariableValue> {
+        Some(match value {
+            ScriptValue::Bool(b) => VariableValue::Bool(b),
+            ScriptValue::Integer(i) => VariableValue::Int(i32::try_from(i).ok()?),
+            ScriptValue::Float(f) => VariableValue::Float(f as f32),
+            ScriptValue::String(s) => VariableValue::String(s.into_owned()),
+            ScriptValue::List(items) => VariableValue::List(
+                items
+                    .into_iter()
+                    .map(|item| match item {
+                        ScriptValue::String(s) => Some(s.into_owned()),
+                        _ => None,
+                    })
+                    .collect::<Option<_>>()?,
+            ),
+            _ => return None,
+        })
+    }
+
+    fn choice_info_to_script_value(choice: &ChoiceInfo) -> ScriptValue {
+        let mut map: HashMap<String, ScriptValue> = HashMap::new();
+        map.insert("index".to_string(), ScriptValue::Integer(choice.index as i64));
+        map.insert("text".to_string(), ScriptValue::String(choice.text.clone().into()));
+        map.insert("tags".to_string(), to_tags(choice.tags.clone()));
+        ScriptValue::Map(map)
+    }
+
     fn to_tags(tags: Vec<String>) -> ScriptValue {
         let tags: Vec<ScriptValue> = tags
             .into_iter()
@@ -225,3 +1077,151 @@ mod lua {
         ScriptValue::List(tags)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_mod_scripting::{
+        asset::Language,
+        bindings::{
+            AppScriptFunctionRegistry, ThreadWorldContainer, WorldContainer, WorldGuard,
+            function::{namespace::Namespace, script_function::FunctionCallContext},
+        },
+    };
+    use std::any::TypeId;
+
+    const LINES: &str = include_str!("../assets/lines.ink.json");
+    const CHOICES: &str = include_str!("../assets/choices.ink.json");
+
+    #[test]
+    fn story_methods_are_registered_with_their_arguments() {
+        let app = crate::tests::app();
+        let registry = app.world().resource::<AppScriptFunctionRegistry>().read();
+        let namespace = Namespace::OnType(TypeId::of::<InkStoryRef>());
+        let names: HashSet<&str> = registry
+            .iter_namespace(namespace)
+            .map(|(key, _)| key.name.as_ref())
+            .collect();
+        for name in [
+            "cont",
+            "can_continue",
+            "get_current_choices",
+            "choose_choice_index",
+        ] {
+            assert!(names.contains(name), "{name} isn't registered");
+        }
+
+        let choose = registry
+            .get_function(namespace, "choose_choice_index")
+            .unwrap();
+        let args: Vec<_> = choose
+            .info
+            .arg_info
+            .iter()
+            .map(|arg| arg.name.as_deref())
+            .collect();
+        // Scripts don't pass the context, but it's listed like the others.
+        assert_eq!(args, [Some("ctx"), Some("self"), Some("index")]);
+        assert_eq!(choose.info.arg_info[2].type_id, TypeId::of::<usize>());
+        assert!(choose.info.docs.is_some());
+        assert!(registry.contains(Namespace::Global, "ink_load"));
+    }
+
+    #[test]
+    fn a_failed_ink_load_leaves_the_world_accessible() {
+        let mut app = crate::tests::app();
+        WorldGuard::with_static_guard(app.world_mut(), |world| {
+            ThreadWorldContainer.set_world(world.clone()).unwrap();
+            let registry = world.script_function_registry();
+            let registry = registry.read();
+            let ink_load = registry
+                .get_function(Namespace::Global, "ink_load")
+                .unwrap();
+            let path = || [ScriptValue::String("lines.ink.json".into())];
+            let context = FunctionCallContext::new(Language::Lua);
+
+            // Fails to claim the world while a resource is borrowed.
+            world
+                .with_resource(|_: &AssetServer| {
+                    assert!(ink_load.call(path(), context.clone()).is_err());
+                })
+                .unwrap();
+            // Then nothing is left claimed.
+            assert!(world.with_global_access(|_| ()).is_ok());
+            assert!(ink_load.call(path(), context).is_ok());
+        });
+    }
+
+    #[test]
+    fn only_forks_are_discarded() {
+        let mut app = crate::tests::app();
+        let id = app.world_mut().spawn(InkStory).id();
+        app.world_mut()
+            .non_send_resource_mut::<InkStories>()
+            .parse_from_str(id, LINES)
+            .unwrap();
+        WorldGuard::with_static_guard(app.world_mut(), |world| {
+            ThreadWorldContainer.set_world(world.clone()).unwrap();
+            let registry = world.script_function_registry();
+            let registry = registry.read();
+            let namespace = Namespace::OnType(TypeId::of::<InkStoryRef>());
+            let call = |name: &str, this: ScriptValue| {
+                registry
+                    .get_function(namespace, name.to_string())
+                    .unwrap()
+                    .call([this], FunctionCallContext::new(Language::Lua))
+            };
+            let story = || InkStoryRef(id).into_script_ref(world.clone()).unwrap();
+
+            assert!(call("discard", story()).is_err());
+            let fork = call("fork", story()).unwrap();
+            assert_eq!(call("discard", fork).unwrap(), ScriptValue::Bool(true));
+            assert_eq!(call("is_loaded", story()).unwrap(), ScriptValue::Bool(true));
+        });
+        assert!(app.world().get_entity(id).is_ok());
+    }
+
+    #[test]
+    fn current_choices_pass_through_the_choice_filter() {
+        let mut app = crate::tests::app();
+        let handle = app
+            .world_mut()
+            .resource_mut::<Assets<InkText>>()
+            .add(InkText(CHOICES.into(), InkFormat::Json));
+        let id = app
+            .world_mut()
+            .spawn((
+                InkLoad(handle),
+                ChoiceFilter::new(|choice| choice.text != "Fine"),
+            ))
+            .id();
+        app.update();
+        app.update();
+        let mut ink_stories = app.world_mut().non_send_resource_mut::<InkStories>();
+        ink_stories.cont(id).unwrap();
+        ink_stories.cont(id).unwrap();
+
+        let choices = WorldGuard::with_static_guard(app.world_mut(), |world| {
+            ThreadWorldContainer.set_world(world.clone()).unwrap();
+            let registry = world.script_function_registry();
+            let story = InkStoryRef(id).into_script_ref(world.clone()).unwrap();
+            registry
+                .read()
+                .get_function(
+                    Namespace::OnType(TypeId::of::<InkStoryRef>()),
+                    "get_current_choices",
+                )
+                .unwrap()
+                .call([story], FunctionCallContext::new(Language::Lua))
+                .unwrap()
+        });
+        let ScriptValue::List(choices) = choices else {
+            panic!("unexpected choices {choices:?}");
+        };
+        let [ScriptValue::Map(tired)] = choices.as_slice() else {
+            panic!("unexpected choices {choices:?}");
+        };
+        assert_eq!(tired["index"], ScriptValue::Integer(0));
+        assert_eq!(tired["text"], ScriptValue::String("Tired".into()));
+    }
+}
@@ -0,0 +1,523 @@
+//! Reads what bladeink doesn't expose out of a story's saved state JSON.
+use super::*;
+use bladeink::story_error::StoryError;
+use serde_json::Value;
+use std::path::PathBuf;
+
+fn saved_state(story: &Story) -> Result<Value, InkError> {
+    serde_json::from_str(&story.save_state()?)
+        .map_err(|err| StoryError::BadJson(err.to_string()).into())
+}
+
+/// Applies `edit` to the saved state and loads it back.
+fn edit_saved_state(story: &mut Story, edit: impl FnOnce(&mut Value)) -> Result<(), InkError> {
+    let mut state = saved_state(story)?;
+    edit(&mut state);
+    story.load_state(&state.to_string())?;
+    Ok(())
+}
+
+fn state_int(story: &Story, key: &str) -> Result<i64, InkError> {
+    saved_state(story)?
+        .get(key)
+        .and_then(Value::as_i64)
+        .ok_or_else(|| StoryError::InvalidStoryState(format!("no {key:?} in saved state")).into())
+}
+
+/// The key of the global variables in saved state.
+const VARIABLES: &str = "variablesState";
+
+/// A tunnel's push-pop type in a saved call stack; functions are `1` and `2`.
+const TUNNEL: u64 = 0;
+
+/// Returns the current thread's call stack elements, outermost first.
+fn call_stack(story: &Story) -> Result<Vec<Value>, InkError> {
+    let state = saved_state(story)?;
+    let flow = state
+        .get("currentFlowName")
+        .and_then(Value::as_str)
+        .and_then(|name| state.get("flows")?.get(name));
+    flow.and_then(|flow| flow.get("callstack")?.get("threads")?.as_array()?.last())
+        .and_then(|thread| thread.get("callstack")?.as_array().cloned())
+        .ok_or_else(|| StoryError::InvalidStoryState("no call stack in saved state".into()).into())
+}
+
+/// Where a current choice was defined and where it leads, as ink's container
+/// paths, e.g., `"knot.0.c-0"`, from [InkStories::choice_paths].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChoicePaths {
+    /// The choice point in the ink that made this choice.
+    pub source_path: String,
+    /// Where the story goes when this choice is chosen.
+    pub target_path: String,
+}
+
+/// What changed between two saved states, from [InkStories::diff_state].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StateDiff {
+    /// The global variables whose values differ, sorted, including any only
+    /// one state has.
+    pub changed_variables: Vec<String>,
+    /// Whether the story is somewhere else: in another flow, at another point
+    /// in its flow, or offering other choices.
+    pub moved: bool,
+}
+
+impl StateDiff {
+    /// Whether nothing this compares changed.
+    pub fn is_empty(&self) -> bool {
+        self.changed_variables.is_empty() && !self.moved
+    }
+}
+
+/// Add to a story entity to seed its story with [InkStories::set_seed]
+/// whenever it's parsed, including on hot reload unless [PreserveState] kept
+/// its state.
+#[derive(Debug, Component, Clone, Copy)]
+pub struct InkSeed(pub u64);
+
+/// A name for a story entity that stays the same across runs, unlike its
+/// [Entity], so save files from [InkStories::save_all] find the right story
+/// after a restart.
+#[derive(Debug, Component, Clone, PartialEq, Eq, Hash)]
+pub struct StoryId(pub String);
+
+/// Add to a story entity with a state from, e.g., [InkStories::save_all] or
+/// [InkSavedState] to continue from it as soon as the story is parsed. It's
+/// removed once applied; if it can't be, the story starts over and raises
+/// [InkEvent::RestoreFailed].
+#[derive(Debug, Component, Clone)]
+pub struct InkRestore(pub String);
+
+/// Insert to save a story's state into its [InkSavedState] every time a choice
+/// is chosen, from Rust or a script, so a crash loses no progress. Persisting
+/// that component is up to you.
+///
+/// Takes effect once [store_auto_saves] has seen it.
+#[derive(Debug, Resource, Clone, Copy, Default)]
+pub struct AutoSaveOnChoice;
+
+/// Insert to save every story with a [StoryId] when the app exits cleanly:
+/// [save_on_exit] hands the states from [InkStories::save_all] to this
+/// callback, e.g., to write them to a save file.
+#[derive(Resource, Clone)]
+pub struct AutoSaveOnExit(pub Arc<dyn Fn(HashMap<String, String>) + Send + Sync>);
+
+impl AutoSaveOnExit {
+    pub fn new(save: impl Fn(HashMap<String, String>) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(save))
+    }
+
+    /// Writes the states as a JSON object of [StoryId]s to states to `path`.
+    pub fn to_file(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        Self::new(move |states| {
+            let result = serde_json::to_string(&states)
+                .map_err(std::io::Error::other)
+                .and_then(|json| std::fs::write(&path, json));
+            if let Err(err) = result {
+                error!("Could not save ink stories to {path:?}: {err}");
+            }
+        })
+    }
+}
+
+impl std::fmt::Debug for AutoSaveOnExit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("AutoSaveOnExit").finish_non_exhaustive()
+    }
+}
+
+/// Saves the stories for [AutoSaveOnExit] once an [AppExit] is sent. Runs in
+/// `Last` so it sees an exit sent earlier in the frame.
+pub fn save_on_exit(
+    mut exits: EventReader<AppExit>,
+    save: Option<Res<AutoSaveOnExit>>,
+    ink_stories: NonSend<InkStories>,
+    ids: Query<(Entity, &StoryId)>,
+) {
+    if exits.is_empty() {
+        return;
+    }
+    exits.clear();
+    let Some(save) = save else {
+        return;
+    };
+    match ink_stories.save_all(ids) {
+        Ok(states) => (save.0)(states),
+        Err(err) => error!("Could not save ink stories on exit: {err}"),
+    }
+}
+
+/// The story's state as of its latest choice, kept by [AutoSaveOnChoice].
+#[derive(Debug, Component, Clone)]
+pub struct InkSavedState(pub String);
+
+/// Puts the states saved on choosing into [InkSavedState]s.
+pub fn store_auto_saves(
+    auto_save: Option<Res<AutoSaveOnChoice>>,
+    mut ink_stories: NonSendMut<InkStories>,
+    mut commands: Commands,
+) {
+    ink_stories.auto_save_on_choice = auto_save.is_some();
+    for (id, state) in ink_stories.auto_saves.drain(..) {
+        commands.entity(id).try_insert(InkSavedState(state));
+    }
+}
+
+impl InkStories {
+    /// Loads an [InkRestore] state into a just parsed story, starting it over
+    /// if that fails.
+    pub(crate) fn restore(
+        &mut self,
+        id: Entity,
+        state: &str,
+        options: &ParseOptionsItem,
+        presets: &Assets<InkVarsAsset>,
+    ) {
+        let Ok(story) = self.get_mut(id) else {
+            return;
+        };
+        let Err(err) = story.load_state(state) else {
+            return;
+        };
+        error!("Could not restore ink state in {id}, starting over: {err}");
+        if let Err(err) = story.reset_state() {
+            warn!("Could not reset ink in {id}: {err}");
+        }
+        options.apply(self, presets, id, true);
+        self.events.push(InkEvent::RestoreFailed {
+            entity: id,
+            message: err.to_string(),
+        });
+    }
+
+    /// Saves the story's state for [store_auto_saves] if [AutoSaveOnChoice]
+    /// is on.
+    pub(crate) fn auto_save(&mut self, id: Entity) -> Result<(), InkError> {
+        if self.auto_save_on_choice {
+            let state = self.get(id)?.save_state()?;
+            self.auto_saves.push((id, state));
+        }
+        Ok(())
+    }
+
+    /// Returns ink's turn index, which goes up by one with each choice taken.
+    /// It starts at `-1`, so it's always one less than ink's `TURNS()`.
+    pub fn current_turn_index(&self, id: Entity) -> Result<i32, InkError> {
+        Ok(state_int(self.get(id)?, "turnIdx")? as i32)
+    }
+
+    /// Returns how deep the story is in tunnels and function calls, `1` at
+    /// the top level, e.g., for a debug overlay on a conversation that won't
+    /// end.
+    pub fn call_stack_depth(&self, id: Entity) -> Result<usize, InkError> {
+        Ok(call_stack(self.get(id)?)?.len())
+    }
+
+    /// Returns where each tunnel the story is in returns to with `->->`,
+    /// outermost first, as ink's container paths, e.g., `"chapter1.0"`.
+    pub fn tunnel_return_paths(&self, id: Entity) -> Result<Vec<String>, InkError> {
+        let elements = call_stack(self.get(id)?)?;
+        // A tunnel returns to where the element below it was when it was
+        // entered, which that element keeps while the tunnel runs.
+        Ok(elements
+            .windows(2)
+            .filter(|pair| pair[1].get("type").and_then(Value::as_u64) == Some(TUNNEL))
+            .filter_map(|pair| pair[0].get("cPath")?.as_str().map(String::from))
+            .collect())
+    }
+
+    /// Starts the story over from the beginning, raising
+    /// [InkEvent::OnStoryReset]. Its variables go back to their initial
+    /// values too.
+    pub fn reset_state(&mut self, id: Entity) -> Result<(), InkError> {
+        let entry = self.entry_mut(id)?;
+        entry.story.reset_state()?;
+        entry.current_line = None;
+        entry.buffered_text.clear();
+        entry.error_log.clear();
+        if let Some(calls) = &entry.async_calls {
+            calls.borrow_mut().clear();
+        }
+        self.events.push(InkEvent::OnStoryReset { entity: id });
+        self.raise_choices_changed(id);
+        Ok(())
+    }
+
+    /// Returns whether each current choice is once-only (`*`) rather than
+    /// sticky (`+`), indexed like [InkStories::current_choices], e.g., to
+    /// draw sticky choices differently.
+    ///
+    /// Bladeink doesn't say, so this looks up where each choice was defined,
+    /// via [InkStories::choice_paths], in the story's JSON. A once-only
+    /// choice already taken is never offered again, so every choice here is
+    /// still available.
+    pub fn choice_once_only(&self, id: Entity) -> Result<Vec<bool>, InkError> {
+        let paths = self.choice_paths(id)?;
+        let sources: Vec<&str> = paths
+            .iter()
+            .map(|paths| paths.source_path.as_str())
+            .collect();
+        compiled::choice_points_once_only(&self.entry(id)?.json, &sources)
+            .into_iter()
+            .zip(&sources)
+            .map(|(once_only, source)| {
+                once_only.ok_or_else(|| {
+                    StoryError::InvalidStoryState(format!("no choice point at {source:?}")).into()
+                })
+            })
+            .collect()
+    }
+
+    /// Compares two states saved with [InkStories::save_state], e.g., to send
+    /// only what changed over the network after a step.
+    pub fn diff_state(old_json: &str, new_json: &str) -> Result<StateDiff, InkError> {
+        let parse = |json: &str| -> Result<Value, InkError> {
+            serde_json::from_str(json).map_err(|err| StoryError::BadJson(err.to_string()).into())
+        };
+        let (old, new) = (parse(old_json)?, parse(new_json)?);
+        let variables = |state: &Value| state.get(VARIABLES).and_then(Value::as_object).cloned();
+        let (old_variables, new_variables) = (
+            variables(&old).unwrap_or_default(),
+            variables(&new).unwrap_or_default(),
+        );
+        let mut changed_variables: Vec<String> = old_variables
+            .keys()
+            .chain(new_variables.keys())
+            .filter(|name| old_variables.get(*name) != new_variables.get(*name))
+            .cloned()
+            .collect();
+        changed_variables.sort();
+        changed_variables.dedup();
+        // The current flow's call stack holds the story's position.
+        let position = |state: &Value| {
+            let name = state.get("currentFlowName");
+            let flow = name
+                .and_then(Value::as_str)
+                .and_then(|name| state.get("flows")?.get(name));
+            (
+                name.cloned(),
+                flow.and_then(|flow| flow.get("callstack")).cloned(),
+                flow.and_then(|flow| flow.get("currentChoices")).cloned(),
+            )
+        };
+        Ok(StateDiff {
+            changed_variables,
+            moved: position(&old) != position(&new),
+        })
+    }
+
+    /// Returns where each current choice was defined and leads, indexed like
+    /// [InkStories::current_choices], e.g., to graph a conversation by
+    /// simulating its choices. This reads a saved state, so it's slower than
+    /// `current_choices`.
+    pub fn choice_paths(&self, id: Entity) -> Result<Vec<ChoicePaths>, InkError> {
+        let story = self.get(id)?;
+        let state = saved_state(story)?;
+        let saved = state
+            .get("currentFlowName")
+            .and_then(Value::as_str)
+            .and_then(|name| {
+                state
+                    .get("flows")?
+                    .get(name)?
+                    .get("currentChoices")?
+                    .as_array()
+            })
+            .ok_or_else(|| StoryError::InvalidStoryState("no choices in saved state".into()))?;
+        // The saved choices include invisible fallbacks that bladeink leaves
+        // out of its current choices, so match its choices to them in order.
+        let mut saved = saved.iter();
+        let mut paths = Vec::new();
+        for choice in story.get_current_choices() {
+            let found = saved.find(|saved| {
+                saved.get("text").and_then(Value::as_str) == Some(choice.text.as_str())
+            });
+            let path = |key| {
+                found
+                    .and_then(|saved| saved.get(key)?.as_str())
+                    .map(String::from)
+                    .ok_or_else(|| StoryError::InvalidStoryState(format!("no {key:?} for choice")))
+            };
+            paths.push(ChoicePaths {
+                source_path: path("originalChoicePath")?,
+                target_path: path("targetPath")?,
+            });
+        }
+        Ok(self
+            .visible_choices(id)?
+            .into_iter()
+            .map(|choice| paths[choice.story_index].clone())
+            .collect())
+    }
+
+    /// Saves the state of every story with a [StoryId] by that id, e.g., for
+    /// a whole-game save file. Pass `ids` as a `&Query<(Entity, &StoryId)>`.
+    pub fn save_all<'a>(
+        &self,
+        ids: impl IntoIterator<Item = (Entity, &'a StoryId)>,
+    ) -> Result<HashMap<String, String>, InkError> {
+        ids.into_iter()
+            .filter_map(|(id, story_id)| Some((story_id, self.stories.get(&id)?)))
+            .map(|(story_id, entry)| Ok((story_id.0.clone(), entry.story.save_state()?)))
+            .collect()
+    }
+
+    /// Restores the states from [InkStories::save_all] into the stories with
+    /// those [StoryId]s, passed as for `save_all`, loading each as
+    /// [InkStories::load_state] does. Returns the ids skipped for having no
+    /// story now.
+    pub fn load_all<'a>(
+        &mut self,
+        mut states: HashMap<String, String>,
+        ids: impl IntoIterator<Item = (Entity, &'a StoryId)>,
+    ) -> Result<Vec<String>, InkError> {
+        for (id, story_id) in ids {
+            if self.stories.contains_key(&id)
+                && let Some(state) = states.remove(&story_id.0)
+            {
+                self.load_state(id, &state)?;
+            }
+        }
+        Ok(states.into_keys().collect())
+    }
+
+    /// Saves the story's state as JSON, e.g., for a save menu; load it back
+    /// with [InkStories::load_state].
+    pub fn save_state(&self, id: Entity) -> Result<String, InkError> {
+        Ok(self.get(id)?.save_state()?)
+    }
+
+    /// Loads a state from [InkStories::save_state]. If the state doesn't fit,
+    /// e.g., it's from another story, the story is reset to its start and the
+    /// error returned.
+    pub fn load_state(&mut self, id: Entity, state: &str) -> Result<(), InkError> {
+        let entry = self.entry_mut(id)?;
+        entry.buffered_text.clear();
+        entry.error_log.clear();
+        if let Some(calls) = &entry.async_calls {
+            calls.borrow_mut().clear();
+        }
+        if let Err(err) = entry.story.load_state(state) {
+            entry.story.reset_state()?;
+            return Err(err.into());
+        }
+        self.raise_choices_changed(id);
+        Ok(())
+    }
+
+    /// Saves only the story's global variables, e.g., for a checkpoint that
+    /// keeps what the player has done but not where they are. Load it back
+    /// with [InkStories::load_variables_only].
+    pub fn save_variables_only(&self, id: Entity) -> Result<String, InkError> {
+        let state = saved_state(self.get(id)?)?;
+        state.get(VARIABLES).map(Value::to_string).ok_or_else(|| {
+            StoryError::InvalidStoryState("no variables in saved state".into()).into()
+        })
+    }
+
+    /// Restores the variables from [InkStories::save_variables_only], leaving
+    /// the story where it is. Variables missing from `variables` go back to
+    /// their initial values. Observers aren't told of the changes.
+    pub fn load_variables_only(&mut self, id: Entity, variables: &str) -> Result<(), InkError> {
+        let variables: Value =
+            serde_json::from_str(variables).map_err(|err| StoryError::BadJson(err.to_string()))?;
+        if !variables.is_object() {
+            return Err(StoryError::BadJson("variables aren't an object".into()).into());
+        }
+        edit_saved_state(self.get_mut(id)?, |state| state[VARIABLES] = variables)
+    }
+
+    /// Loads a full saved state except for its variables, which keep their
+    /// current values, e.g., to go back to a checkpoint's place and visit
+    /// counts without undoing what the player has done since.
+    pub fn load_state_keeping_variables(
+        &mut self,
+        id: Entity,
+        state: &str,
+    ) -> Result<(), InkError> {
+        let mut state: Value =
+            serde_json::from_str(state).map_err(|err| StoryError::BadJson(err.to_string()))?;
+        if !state.is_object() {
+            return Err(StoryError::InvalidStoryState("state isn't an object".into()).into());
+        }
+        if let Some(variables) = saved_state(self.get(id)?)?.get_mut(VARIABLES) {
+            state[VARIABLES] = variables.take();
+        }
+        self.load_state(id, &state.to_string())
+    }
+
+    /// Seeds the story's `RANDOM()` and shuffles, so the same seed and choices
+    /// play the same way every time, e.g., for replay tests. Only the seed
+    /// modulo `i32::MAX` counts, as ink's seed is an `int`.
+    ///
+    /// Seed before the first continue for a whole playthrough to repeat.
+    /// Seeding later can't undo what was rolled already, and shuffles also go
+    /// by visit counts, so only runs from the same state play alike.
+    ///
+    /// Resetting the story picks a new random seed.
+    pub fn set_seed(&mut self, id: Entity, seed: u64) -> Result<(), InkError> {
+        let seed = (seed % i32::MAX as u64) as i64;
+        edit_saved_state(self.get_mut(id)?, |state| {
+            state["storySeed"] = seed.into();
+            state["previousRandom"] = 0.into();
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LINES: &str = include_str!("../assets/lines.ink.json");
+    const RANDOM: &str = include_str!("../assets/random.ink.json");
+
+    /// Plays the seeded random story through.
+    fn play(seed: u64) -> Vec<String> {
+        let mut ink_stories = InkStories::default();
+        let id = Entity::from_raw(0);
+        ink_stories.parse_from_str(id, RANDOM).unwrap();
+        ink_stories.set_seed(id, seed).unwrap();
+        (0..3).map(|_| ink_stories.cont(id).unwrap()).collect()
+    }
+
+    #[test]
+    fn a_seeded_story_plays_the_same_twice() {
+        let first = play(42);
+        assert_eq!(first.len(), 3);
+        assert_eq!(first, play(42));
+        assert_ne!(first, play(7));
+    }
+
+    #[test]
+    fn load_all_restores_each_story() {
+        let mut ink_stories = InkStories::default();
+        let (id, gone) = (Entity::from_raw(0), Entity::from_raw(1));
+        ink_stories.parse_from_str(id, LINES).unwrap();
+        ink_stories.cont(id).unwrap();
+        let story_id = StoryId("intro".into());
+        let mut states = ink_stories.save_all([(id, &story_id)]).unwrap();
+
+        assert_eq!(ink_stories.cont(id).unwrap(), "World\n");
+        states.insert("gone".into(), String::new());
+        let gone_id = StoryId("gone".into());
+        let skipped = ink_stories
+            .load_all(states, [(id, &story_id), (gone, &gone_id)])
+            .unwrap();
+        assert_eq!(skipped, ["gone"]);
+        assert_eq!(ink_stories.cont(id).unwrap(), "World\n");
+    }
+
+    #[test]
+    fn a_state_that_isnt_an_object_is_not_loaded() {
+        let mut ink_stories = InkStories::default();
+        let id = Entity::from_raw(0);
+        ink_stories.parse_from_str(id, LINES).unwrap();
+        assert!(matches!(
+            ink_stories.load_state_keeping_variables(id, "[]"),
+            Err(InkError::StoryError(StoryError::InvalidStoryState(_)))
+        ));
+        assert_eq!(ink_stories.cont(id).unwrap(), "Hello\n");
+    }
+}
@@ -0,0 +1,68 @@
+use super::*;
+
+/// Add to a story entity to have each [InkStories::cont] also raise
+/// [InkEvent::OnStep] with everything the line changed, e.g., to send one
+/// message per step over the network or record a replay. Off by default since
+/// it reads the story's choices, path, and perhaps variables every line.
+#[derive(Debug, Component, Clone, Default)]
+pub struct InkStepEvents {
+    /// Whether [StepSnapshot::variables] is filled in; every global variable
+    /// can be a lot to copy each line.
+    pub variables: bool,
+}
+
+/// Everything one line of a [InkStepEvents] story produced, as of right
+/// after it was continued.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StepSnapshot {
+    pub entity: Entity,
+    pub text: String,
+    pub tags: Vec<String>,
+    /// The choices offered after this line, empty unless the story stopped.
+    pub choices: Vec<ChoiceInfo>,
+    /// Where the story is; see [InkStories::current_path].
+    pub path: Option<String>,
+    /// Every global variable, if [InkStepEvents::variables] asks for them.
+    pub variables: Option<HashMap<String, VariableValue>>,
+}
+
+/// Hands each story whether its [InkStepEvents] asks for variables, or stops
+/// its step events once removed.
+pub fn sync_step_events(
+    mut ink_stories: NonSendMut<InkStories>,
+    query: Query<(Entity, Ref<InkStepEvents>, Ref<InkStory>)>,
+    mut removed: RemovedComponents<InkStepEvents>,
+) {
+    for id in removed.read() {
+        if let Ok(entry) = ink_stories.entry_mut(id) {
+            entry.step_events = None;
+        }
+    }
+    for (id, step_events, story) in &query {
+        if !step_events.is_changed() && !story.is_added() {
+            continue;
+        }
+        if let Ok(entry) = ink_stories.entry_mut(id) {
+            entry.step_events = Some(step_events.variables);
+        }
+    }
+}
+
+impl InkStories {
+    /// Raises [InkEvent::OnStep] for the line just continued if the story has
+    /// an [InkStepEvents].
+    pub(crate) fn raise_step(&mut self, id: Entity, text: &str, tags: &[String]) {
+        let Some(variables) = self.entry(id).ok().and_then(|entry| entry.step_events) else {
+            return;
+        };
+        let snapshot = StepSnapshot {
+            entity: id,
+            text: text.to_string(),
+            tags: tags.to_vec(),
+            choices: self.current_choices(id).unwrap_or_default(),
+            path: self.current_path(id).ok().flatten(),
+            variables: variables.then(|| self.all_variables(id).unwrap_or_default()),
+        };
+        self.events.push(InkEvent::OnStep(snapshot));
+    }
+}
@@ -0,0 +1,213 @@
+use super::*;
+
+/// Splits `key: value` tags into a map of trimmed keys to trimmed values.
+///
+/// Only the first colon separates the key, so `# url: http://example.com`
+/// has the value `http://example.com`. Tags without a colon are left out; see
+/// [bare_tags].
+pub fn parse_tags(tags: &[String]) -> HashMap<String, String> {
+    tags.iter()
+        .filter_map(|tag| tag.split_once(':'))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .collect()
+}
+
+/// Returns the trimmed tags that have no `key: value` colon.
+pub fn bare_tags(tags: &[String]) -> Vec<String> {
+    tags.iter()
+        .filter(|tag| !tag.contains(':'))
+        .map(|tag| tag.trim().to_string())
+        .collect()
+}
+
+/// Insert as a resource to check each story as it's parsed, including on hot
+/// reload, for lines missing a tag with one of `required_keys`, e.g.,
+/// `speaker` for `# speaker: Ann`. Each such line raises an
+/// [InkEvent::Warning]; see [InkStories::lint_tags] for which lines are
+/// checked.
+#[derive(Debug, Resource, Clone, Default)]
+pub struct TagLint {
+    pub required_keys: Vec<String>,
+}
+
+/// A story's metadata, inserted when it's parsed and refreshed on hot reload,
+/// e.g., to list conversations by their `# title:` without the live story.
+#[derive(Debug, Component, Clone, Default)]
+pub struct InkMeta {
+    /// The story's global tags, empty if it has none; see
+    /// [InkStories::global_tags].
+    pub tags: Vec<String>,
+    /// How many warnings the story reported while parsing, e.g., that it was
+    /// compiled for another version of ink, for an at-a-glance health badge.
+    /// Warnings while playing are raised as [InkEvent::Warning] instead.
+    pub warning_count: usize,
+    /// How many errors the story reported while parsing without failing to
+    /// parse.
+    pub error_count: usize,
+    /// The `inkVersion` the story was compiled with; see
+    /// [InkStories::ink_version].
+    pub ink_version: Option<i32>,
+}
+
+impl InkStories {
+    /// Returns the story's [InkMeta] as of its latest parse.
+    pub fn meta(&self, id: Entity) -> Result<InkMeta, InkError> {
+        let entry = self.entry(id)?;
+        let (warning_count, error_count) = entry.parse_issues;
+        Ok(InkMeta {
+            tags: entry.story.get_global_tags()?,
+            warning_count,
+            error_count,
+            ink_version: compiled::ink_version(&entry.json),
+        })
+    }
+
+    /// Returns the `inkVersion` the story was compiled with, e.g., to warn
+    /// when it isn't bladeink's `story::INK_VERSION_CURRENT`. Stories older
+    /// than `story::INK_VERSION_MINIMUM_COMPATIBLE` don't parse at all.
+    pub fn ink_version(&self, id: Entity) -> Result<i32, InkError> {
+        compiled::ink_version(&self.entry(id)?.json)
+            .ok_or_else(|| StoryError::BadJson("no inkVersion".into()).into())
+    }
+
+    /// Returns the tags at the very top of the story, e.g., `# title: ...`,
+    /// which don't change as it plays.
+    pub fn global_tags(&self, id: Entity) -> Result<Vec<String>, InkError> {
+        Ok(self.get(id)?.get_global_tags()?)
+    }
+
+    /// Returns the tags of the line produced by the most recent
+    /// [InkStories::cont] only; tags of earlier lines are gone, so a line
+    /// without tags has none here. As in ink, the first line's tags include
+    /// the global tags.
+    pub fn current_tags(&mut self, id: Entity) -> Result<Vec<String>, InkError> {
+        Ok(self.get_mut(id)?.get_current_tags()?)
+    }
+
+    /// Returns the id of the line produced by the most recent
+    /// [InkStories::cont] from its [InkStories::line_id_tag] tag, e.g.,
+    /// `greeting_01` for `# id: greeting_01`, to look up its translation.
+    /// Returns `None` if the line has no such tag.
+    pub fn current_line_id(&mut self, id: Entity) -> Result<Option<String>, InkError> {
+        let tags = self.current_tags(id)?;
+        Ok(parse_tags(&tags).remove(&self.line_id_tag))
+    }
+
+    /// Returns a message for each line missing a `key: value` tag with one of
+    /// `required_keys`, e.g., to hold a team to `# speaker:` on every line.
+    ///
+    /// Ink can't be checked without playing it, so this plays a
+    /// [fork](InkStories::fork) from where the story is, taking the first
+    /// choice at each choice point, until it ends, fails, or has continued
+    /// [InkStories::max_continues] lines. Lines on other branches go unchecked.
+    pub fn lint_tags(&self, id: Entity, required_keys: &[String]) -> Result<Vec<String>, InkError> {
+        let mut story = self.fork(id)?;
+        let mut messages = Vec::new();
+        for _ in 0..self.max_continues {
+            if !story.can_continue() {
+                if story.get_current_choices().is_empty() {
+                    break;
+                }
+                story.choose_choice_index(0)?;
+                continue;
+            }
+            let Ok(line) = story.cont() else {
+                break;
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            let tags = parse_tags(&story.get_current_tags()?);
+            let missing: Vec<&str> = required_keys
+                .iter()
+                .filter(|key| !tags.contains_key(*key))
+                .map(String::as_str)
+                .collect();
+            if !missing.is_empty() {
+                let message = format!("line {:?} has no {} tag", line.trim(), missing.join(", "));
+                // A story that loops would report its lines again.
+                if !messages.contains(&message) {
+                    messages.push(message);
+                }
+            }
+        }
+        Ok(messages)
+    }
+
+    /// Raises an [InkEvent::Warning] for each line [InkStories::lint_tags]
+    /// finds missing a tag `lint` requires.
+    pub(crate) fn raise_tag_lint(&mut self, id: Entity, lint: Option<&TagLint>) {
+        let Some(lint) = lint.filter(|lint| !lint.required_keys.is_empty()) else {
+            return;
+        };
+        match self.lint_tags(id, &lint.required_keys) {
+            Ok(messages) => {
+                for message in messages {
+                    self.events.push(InkEvent::Warning {
+                        entity: id,
+                        message,
+                    });
+                }
+            }
+            Err(err) => warn!("Could not lint ink tags in {id}: {err}"),
+        }
+    }
+
+    /// Returns the tags the next [InkStories::cont] would produce without
+    /// advancing the story, e.g., to tell a `# nonskip` line before showing it.
+    /// Empty if the story can't continue.
+    ///
+    /// Ink only knows a line's tags once it has continued over it, so this
+    /// continues a [fork](InkStories::fork) of the story. The fork has no
+    /// external functions bound, so a line calling one without an ink fallback
+    /// fails here; use the tags on [StepResult] instead for such lines.
+    pub fn peek_tags(&self, id: Entity) -> Result<Vec<String>, InkError> {
+        let mut story = self.fork(id)?;
+        if !story.can_continue() {
+            return Ok(Vec::new());
+        }
+        story.cont()?;
+        Ok(story.get_current_tags()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TAGS: &str = include_str!("../assets/tags.ink.json");
+
+    #[test]
+    fn global_tags_are_kept_apart_from_line_tags() {
+        let mut ink_stories = InkStories::default();
+        let id = Entity::from_raw(0);
+        ink_stories.parse_from_str(id, TAGS).unwrap();
+        let global = vec![
+            "title: Tag Demo".to_string(),
+            "author: bevy_ink".to_string(),
+        ];
+        assert_eq!(ink_stories.global_tags(id).unwrap(), global);
+
+        assert_eq!(ink_stories.cont(id).unwrap(), "Hello.\n");
+        let mut first = global.clone();
+        first.push("mood: happy".to_string());
+        assert_eq!(ink_stories.current_tags(id).unwrap(), first);
+
+        assert_eq!(ink_stories.cont(id).unwrap(), "Goodbye.\n");
+        assert_eq!(ink_stories.current_tags(id).unwrap(), ["mood: sad", "loud"]);
+        assert_eq!(ink_stories.global_tags(id).unwrap(), global);
+    }
+
+    #[test]
+    fn a_tagless_line_clears_the_tags() {
+        let mut ink_stories = InkStories::default();
+        let id = Entity::from_raw(0);
+        ink_stories.parse_from_str(id, TAGS).unwrap();
+        ink_stories.cont(id).unwrap();
+        ink_stories.cont(id).unwrap();
+        assert_eq!(ink_stories.current_tags(id).unwrap(), ["mood: sad", "loud"]);
+
+        assert_eq!(ink_stories.cont(id).unwrap(), "The end.\n");
+        assert!(ink_stories.current_tags(id).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,123 @@
+use super::*;
+use std::collections::VecDeque;
+
+/// Add to a story entity to snapshot its state before each
+/// [InkStories::cont] and choice, so [InkStories::undo] can step back, e.g.,
+/// in a tool for testing branches. Ink can't run backward, so each step costs
+/// a saved state; `depth` bounds how many are kept.
+///
+/// The snapshots are kept with the story in [InkStories]. A hot reload drops
+/// them, since they may not fit the new story.
+#[derive(Debug, Component, Clone)]
+pub struct InkStateStack {
+    pub depth: usize,
+}
+
+impl Default for InkStateStack {
+    fn default() -> Self {
+        Self { depth: 32 }
+    }
+}
+
+/// A story's snapshots for [InkStories::undo], newest last.
+pub(crate) struct UndoStack {
+    pub(crate) depth: usize,
+    /// Each saved state with the current and buffered text then.
+    snapshots: VecDeque<(String, Option<String>, String)>,
+}
+
+impl UndoStack {
+    pub(crate) fn new(depth: usize) -> Self {
+        Self {
+            depth,
+            snapshots: VecDeque::new(),
+        }
+    }
+}
+
+/// Hands each story its [InkStateStack] depth, or drops its snapshots once
+/// removed.
+pub fn sync_state_stacks(
+    mut ink_stories: NonSendMut<InkStories>,
+    query: Query<(Entity, Ref<InkStateStack>, Ref<InkStory>)>,
+    mut removed: RemovedComponents<InkStateStack>,
+) {
+    for id in removed.read() {
+        if let Ok(entry) = ink_stories.entry_mut(id) {
+            entry.undo_stack = None;
+        }
+    }
+    for (id, stack, story) in &query {
+        if !stack.is_changed() && !story.is_added() {
+            continue;
+        }
+        if let Ok(entry) = ink_stories.entry_mut(id) {
+            let undo = entry
+                .undo_stack
+                .get_or_insert_with(|| UndoStack::new(stack.depth));
+            undo.depth = stack.depth;
+            while undo.snapshots.len() > undo.depth {
+                undo.snapshots.pop_front();
+            }
+        }
+    }
+}
+
+impl InkEntry {
+    /// Snapshots the story for [InkStories::undo] if it has an
+    /// [InkStateStack].
+    pub(crate) fn snapshot(&mut self) -> Result<(), InkError> {
+        let Some(undo) = &mut self.undo_stack else {
+            return Ok(());
+        };
+        if undo.depth == 0 {
+            return Ok(());
+        }
+        if undo.snapshots.len() >= undo.depth {
+            undo.snapshots.pop_front();
+        }
+        undo.snapshots.push_back((
+            self.story.save_state()?,
+            self.current_line.clone(),
+            self.buffered_text.clone(),
+        ));
+        Ok(())
+    }
+
+    /// Drops the latest snapshot, taken for a step that didn't happen.
+    pub(crate) fn unsnapshot(&mut self) {
+        if let Some(undo) = &mut self.undo_stack {
+            undo.snapshots.pop_back();
+        }
+    }
+}
+
+impl InkStories {
+    /// Steps the story back to before its latest [InkStories::cont] or choice
+    /// by loading the snapshot its [InkStateStack] took then. Returns whether
+    /// there was one to go back to.
+    pub fn undo(&mut self, id: Entity) -> Result<bool, InkError> {
+        let entry = self.entry_mut(id)?;
+        let Some((state, current_line, buffered_text)) = entry
+            .undo_stack
+            .as_mut()
+            .and_then(|undo| undo.snapshots.pop_back())
+        else {
+            return Ok(false);
+        };
+        entry.story.load_state(&state)?;
+        entry.current_line = current_line;
+        entry.buffered_text = buffered_text;
+        self.raise_choices_changed(id);
+        Ok(true)
+    }
+
+    /// Returns how many steps [InkStories::undo] can go back.
+    pub fn undo_depth(&self, id: Entity) -> Result<usize, InkError> {
+        Ok(self
+            .entry(id)?
+            .undo_stack
+            .as_ref()
+            .map_or(0, |undo| undo.snapshots.len()))
+    }
+}
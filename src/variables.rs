@@ -0,0 +1,622 @@
+use super::*;
+use bevy::reflect::DynamicStruct;
+use bladeink::{story::variable_observer::VariableObserver, value_type::ValueType};
+use std::mem::discriminant;
+
+/// An ink variable's value.
+///
+/// Unlike bladeink's [ValueType], this is `Send`, `Sync`, and reflectable, so
+/// it can live in components, events, and resources.
+#[derive(Debug, Clone, PartialEq, Reflect, Serialize, Deserialize)]
+pub enum VariableValue {
+    Bool(bool),
+    Int(i32),
+    Float(f32),
+    String(String),
+    /// A list's items by full name, e.g., `"Colors.red"`, ordered by value.
+    List(Vec<String>),
+    /// The path of a divert target, e.g., `"knot.stitch"`.
+    DivertTarget(String),
+}
+
+impl VariableValue {
+    /// Returns `None` for variable pointers, which only exist as temporary
+    /// `ref` parameters and never as global variables.
+    pub fn from_value_type(value: &ValueType) -> Option<Self> {
+        Some(match value {
+            ValueType::Bool(b) => VariableValue::Bool(*b),
+            ValueType::Int(i) => VariableValue::Int(*i),
+            ValueType::Float(f) => VariableValue::Float(*f),
+            ValueType::String(s) => VariableValue::String(s.string.clone()),
+            ValueType::List(list) => {
+                let mut items: Vec<_> = list.items.iter().collect();
+                items.sort_by(|a, b| {
+                    a.1.cmp(b.1)
+                        .then_with(|| a.0.get_full_name().cmp(&b.0.get_full_name()))
+                });
+                VariableValue::List(
+                    items
+                        .into_iter()
+                        .map(|(item, _)| item.get_full_name())
+                        .collect(),
+                )
+            }
+            ValueType::DivertTarget(path) => VariableValue::DivertTarget(path.to_string()),
+            ValueType::VariablePointer(_) => return None,
+        })
+    }
+
+    /// Returns `None` for lists and divert targets, which can only be made
+    /// from a story; see [InkStories::set_variable].
+    pub(crate) fn to_value_type(&self) -> Option<ValueType> {
+        Some(match self {
+            VariableValue::Bool(b) => ValueType::from(*b),
+            VariableValue::Int(i) => ValueType::from(*i),
+            VariableValue::Float(f) => ValueType::from(*f),
+            VariableValue::String(s) => ValueType::from(s.as_str()),
+            VariableValue::List(_) | VariableValue::DivertTarget(_) => return None,
+        })
+    }
+}
+
+/// Returns `value` as the type of the variable `name`'s `current` value.
+fn coerce(
+    name: &str,
+    current: &VariableValue,
+    value: &VariableValue,
+) -> Result<VariableValue, InkError> {
+    use VariableValue::*;
+    match (current, value) {
+        (Float(_), Int(i)) => Ok(Float(*i as f32)),
+        (Int(_), Float(f))
+            if f.fract() == 0.0 && (i32::MIN as f32..=i32::MAX as f32).contains(f) =>
+        {
+            Ok(Int(*f as i32))
+        }
+        (Bool(_), Bool(_))
+        | (Int(_), Int(_))
+        | (Float(_), Float(_))
+        | (String(_), String(_))
+        | (List(_), List(_))
+        | (DivertTarget(_), DivertTarget(_)) => Ok(value.clone()),
+        _ => Err(InkError::VariableTypeMismatch {
+            name: name.to_string(),
+            expected: match current {
+                Bool(_) => "a bool",
+                Int(_) => "an int",
+                Float(_) => "a float",
+                String(_) => "a string",
+                List(_) => "a list",
+                DivertTarget(_) => "a divert target",
+            },
+        }),
+    }
+}
+
+/// A type an ink variable can be read as with [InkStories::get_variable_as],
+/// by converting or parsing its value.
+pub trait FromInkVariable: Sized {
+    /// Describes what values convert, for [InkError::VariableTypeMismatch].
+    const EXPECTED: &'static str;
+
+    fn from_ink_variable(value: &VariableValue) -> Option<Self>;
+}
+
+impl FromInkVariable for bool {
+    const EXPECTED: &'static str = "a bool";
+
+    fn from_ink_variable(value: &VariableValue) -> Option<Self> {
+        match value {
+            VariableValue::Bool(b) => Some(*b),
+            VariableValue::String(s) => s.trim().parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+impl FromInkVariable for i64 {
+    const EXPECTED: &'static str = "an integer";
+
+    fn from_ink_variable(value: &VariableValue) -> Option<Self> {
+        match value {
+            VariableValue::Int(i) => Some((*i).into()),
+            VariableValue::String(s) => s.trim().parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+impl FromInkVariable for f64 {
+    const EXPECTED: &'static str = "a number";
+
+    fn from_ink_variable(value: &VariableValue) -> Option<Self> {
+        match value {
+            VariableValue::Int(i) => Some((*i).into()),
+            VariableValue::Float(f) => Some((*f).into()),
+            VariableValue::String(s) => s.trim().parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+impl FromInkVariable for Vec2 {
+    const EXPECTED: &'static str = r#"a string of two numbers like "3,4""#;
+
+    fn from_ink_variable(value: &VariableValue) -> Option<Self> {
+        let VariableValue::String(s) = value else {
+            return None;
+        };
+        let (x, y) = s.split_once(',')?;
+        Some(Vec2::new(x.trim().parse().ok()?, y.trim().parse().ok()?))
+    }
+}
+
+impl FromInkVariable for Color {
+    const EXPECTED: &'static str = r##"a hex color string like "#ff00aa""##;
+
+    fn from_ink_variable(value: &VariableValue) -> Option<Self> {
+        let VariableValue::String(s) = value else {
+            return None;
+        };
+        Srgba::hex(s.trim()).ok().map(Color::from)
+    }
+}
+
+/// Add to a story entity to set these variables as soon as its story is
+/// parsed, before it gets [InkStory], e.g., an NPC's name for its lines to
+/// use. Applied again whenever a hot reload starts the story over.
+#[derive(Debug, Component, Clone, Default)]
+pub struct InkInitVars(pub HashMap<String, VariableValue>);
+
+/// Add to a story entity to mirror its global variables, e.g., so they show
+/// up in `bevy-inspector-egui`. Kept in sync by [sync_ink_variables].
+#[derive(Debug, Component, Clone, Default, Reflect)]
+#[reflect(Component, Default)]
+pub struct InkVariables {
+    pub values: HashMap<String, VariableValue>,
+    /// Also copy values changed here into the story, e.g., to live-tune them
+    /// from the inspector. Off by default so the mirror never fights the
+    /// story.
+    pub write_back: bool,
+    /// The values as last copied from the story, to tell which ones were
+    /// changed here since.
+    #[reflect(ignore)]
+    synced: HashMap<String, VariableValue>,
+}
+
+impl InkVariables {
+    /// A mirror that also writes changes back to the story.
+    pub fn two_way() -> Self {
+        Self {
+            write_back: true,
+            ..default()
+        }
+    }
+
+    /// The values to write into a story as it's first parsed: those set at
+    /// spawn when writing back, before the mirror has synced at all.
+    pub(crate) fn initial_values(&self) -> Option<&HashMap<String, VariableValue>> {
+        (self.write_back && self.synced.is_empty()).then_some(&self.values)
+    }
+}
+
+/// Refreshes every [InkVariables] from its story, first writing back the
+/// values changed in it when [InkVariables::write_back] is set.
+pub fn sync_ink_variables(
+    mut ink_stories: NonSendMut<InkStories>,
+    mut query: Query<(Entity, &mut InkVariables), With<InkStory>>,
+) {
+    for (id, mut variables) in &mut query {
+        if variables.write_back && variables.is_changed() {
+            for (name, value) in &variables.values {
+                if variables.synced.get(name) == Some(value) {
+                    continue;
+                }
+                if let Err(err) = ink_stories.set_variable(id, name, value) {
+                    warn!("Could not set ink variable {name:?} in {id}: {err}");
+                }
+            }
+        }
+        let Ok(values) = ink_stories.all_variables(id) else {
+            continue;
+        };
+        if variables.values != values {
+            variables.values = values.clone();
+        }
+        // Doesn't count as a change here, or we'd write back every frame.
+        variables.bypass_change_detection().synced = values;
+    }
+}
+
+/// Queues each change as an [InkEvent::VariableChanged] for the story's
+/// next event flush.
+struct ChangeForwarder {
+    entity: Entity,
+    events: Rc<RefCell<Vec<InkEvent>>>,
+}
+
+impl VariableObserver for ChangeForwarder {
+    fn changed(&mut self, name: &str, value: &ValueType) {
+        if let Some(value) = VariableValue::from_value_type(value) {
+            self.events.borrow_mut().push(InkEvent::VariableChanged {
+                entity: self.entity,
+                name: name.to_string(),
+                value,
+            });
+        }
+    }
+}
+
+impl InkStories {
+    /// Returns the names of the story's global variables in declaration order.
+    ///
+    /// These are only the variables declared with `VAR` (and lists declared
+    /// with `LIST`); ink keeps no other globals.
+    pub fn variable_names(&self, id: Entity) -> Result<Vec<String>, InkError> {
+        Ok(self.entry(id)?.variable_names.clone())
+    }
+
+    /// Returns the value of the global variable `name`.
+    pub fn get_variable(&self, id: Entity, name: &str) -> Result<VariableValue, InkError> {
+        self.get(id)?
+            .get_variable(name)
+            .as_ref()
+            .and_then(VariableValue::from_value_type)
+            .ok_or_else(|| InkError::NoSuchVariable(name.to_string()))
+    }
+
+    /// Returns the global variable `name` read as a `T`, e.g., a [Color] from
+    /// `VAR tint = "#ff00aa"`.
+    pub fn get_variable_as<T: FromInkVariable>(
+        &self,
+        id: Entity,
+        name: &str,
+    ) -> Result<T, InkError> {
+        T::from_ink_variable(&self.get_variable(id, name)?).ok_or_else(|| {
+            InkError::VariableTypeMismatch {
+                name: name.to_string(),
+                expected: T::EXPECTED,
+            }
+        })
+    }
+
+    /// Returns the global variable `name` as JSON, e.g., for tooling that
+    /// passes values on without matching on their type. Strings and divert
+    /// targets become JSON strings, lists arrays of full item names, and
+    /// infinite or NaN floats `null`.
+    pub fn variable_as_json(&self, id: Entity, name: &str) -> Result<String, InkError> {
+        Ok(variable_to_json(&self.get_variable(id, name)?).to_string())
+    }
+
+    /// Whether the story declares a global variable `name`, e.g., to branch on
+    /// which version of a story is loaded.
+    pub fn has_variable(&self, id: Entity, name: &str) -> Result<bool, InkError> {
+        Ok(self.get(id)?.get_variable(name).is_some())
+    }
+
+    /// Returns every global variable by name with its current value.
+    pub fn all_variables(&self, id: Entity) -> Result<HashMap<String, VariableValue>, InkError> {
+        let entry = self.entry(id)?;
+        Ok(entry
+            .variable_names
+            .iter()
+            .filter_map(|name| {
+                let value = entry.story.get_variable(name)?;
+                VariableValue::from_value_type(&value).map(|value| (name.clone(), value))
+            })
+            .collect())
+    }
+
+    /// Returns the story's global variables as a [DynamicStruct] with one field
+    /// per variable, for reflection-based inspectors and serializers.
+    ///
+    /// Ink values map to fields as follows:
+    ///
+    /// | ink            | field         |
+    /// |----------------|---------------|
+    /// | bool           | `bool`        |
+    /// | int            | `i32`         |
+    /// | float          | `f32`         |
+    /// | string         | `String`      |
+    /// | list           | `Vec<String>` of full item names, e.g., `"Colors.red"` |
+    /// | divert target  | `String` path |
+    pub fn variables_as_reflect(&self, id: Entity) -> Result<DynamicStruct, InkError> {
+        let entry = self.entry(id)?;
+        let mut variables = DynamicStruct::default();
+        for name in &entry.variable_names {
+            let Some(value) = entry.story.get_variable(name) else {
+                continue;
+            };
+            match VariableValue::from_value_type(&value) {
+                Some(VariableValue::Bool(b)) => variables.insert(name, b),
+                Some(VariableValue::Int(i)) => variables.insert(name, i),
+                Some(VariableValue::Float(f)) => variables.insert(name, f),
+                Some(VariableValue::String(s)) | Some(VariableValue::DivertTarget(s)) => {
+                    variables.insert(name, s)
+                }
+                Some(VariableValue::List(items)) => variables.insert(name, items),
+                None => {}
+            }
+        }
+        Ok(variables)
+    }
+
+    /// Sets a global variable, e.g., from an inspector or a debug console.
+    ///
+    /// A list is set by item names, full like `"Colors.red"` or bare like
+    /// `"red"`, from any of the story's lists.
+    /// Divert targets can't be set this way.
+    ///
+    /// Returns the value stored. A whole number set to a float variable is
+    /// stored as a float, and a whole float set to an int variable as an int;
+    /// any other change of type errors with [InkError::VariableTypeMismatch].
+    pub fn set_variable(
+        &mut self,
+        id: Entity,
+        name: &str,
+        value: &VariableValue,
+    ) -> Result<VariableValue, InkError> {
+        let entry = self.entry_mut(id)?;
+        let current = entry
+            .story
+            .get_variable(name)
+            .as_ref()
+            .and_then(VariableValue::from_value_type)
+            .ok_or_else(|| InkError::NoSuchVariable(name.to_string()))?;
+        let value = &coerce(name, &current, value)?;
+        let value = match value {
+            VariableValue::List(names) => {
+                let Some(ValueType::List(mut list)) = entry.story.get_variable(name) else {
+                    return Err(InkError::CannotSetVariable(name.to_string()));
+                };
+                // An item may come from any list, not only those the variable
+                // holds items of now, so look through the definitions behind
+                // every list variable, which include each `LIST`'s own.
+                let mut definitions = Vec::new();
+                for other in &entry.variable_names {
+                    if let Some(ValueType::List(other)) = entry.story.get_variable(other) {
+                        definitions.extend(other.origins.borrow().iter().cloned());
+                    }
+                }
+                list.items.clear();
+                for item_name in names {
+                    let (item, value) = definitions
+                        .iter_mut()
+                        .find_map(|definition| {
+                            definition
+                                .get_items()
+                                .iter()
+                                .find(|(item, _)| {
+                                    item.get_full_name() == *item_name
+                                        || item.get_item_name() == item_name
+                                })
+                                .map(|(item, value)| (item.clone(), *value))
+                        })
+                        .ok_or_else(|| InkError::NoSuchListItem(item_name.clone()))?;
+                    list.items.insert(item, value);
+                }
+                let mut origins = list.origins.borrow_mut();
+                for definition in &definitions {
+                    let name = definition.get_name();
+                    if list
+                        .items
+                        .keys()
+                        .any(|item| item.get_origin_name().is_some_and(|origin| origin == name))
+                        && !origins.iter().any(|origin| origin.get_name() == name)
+                    {
+                        origins.push(definition.clone());
+                    }
+                }
+                drop(origins);
+                ValueType::from(list)
+            }
+            value => value
+                .to_value_type()
+                .ok_or_else(|| InkError::CannotSetVariable(name.to_string()))?,
+        };
+        entry.story.set_variable(name, &value)?;
+        self.raise_story_events(id);
+        self.get_variable(id, name)
+    }
+
+    /// Sets several global variables like [InkStories::set_variable], e.g., to
+    /// set up a story's state from a script in one call, and returns the
+    /// values stored. Observers are told of each change.
+    ///
+    /// Either all are set or, if any fails, none are: the story goes back to
+    /// how it was, no [InkEvent::VariableChanged] is raised, and the error is
+    /// returned.
+    pub fn set_variables(
+        &mut self,
+        id: Entity,
+        variables: &HashMap<String, VariableValue>,
+    ) -> Result<HashMap<String, VariableValue>, InkError> {
+        let before = self.get(id)?.save_state()?;
+        let events = self.events.len();
+        let mut names: Vec<&String> = variables.keys().collect();
+        names.sort();
+        let mut stored = HashMap::new();
+        for name in names {
+            match self.set_variable(id, name, &variables[name]) {
+                Ok(value) => {
+                    stored.insert(name.clone(), value);
+                }
+                Err(err) => {
+                    self.events.truncate(events);
+                    self.get_mut(id)?.load_state(&before)?;
+                    return Err(err);
+                }
+            }
+        }
+        Ok(stored)
+    }
+
+    /// Raises [InkEvent::VariableChanged] whenever the global variable `name`
+    /// changes, e.g., to update a HUD without polling. Observing a variable
+    /// twice does nothing more; a hot reload drops observers.
+    pub fn observe_variable(&mut self, id: Entity, name: &str) -> Result<(), InkError> {
+        let entry = self.entry_mut(id)?;
+        if entry.observed.contains(name) {
+            return Ok(());
+        }
+        let observer = ChangeForwarder {
+            entity: id,
+            events: entry.story_events.clone(),
+        };
+        entry
+            .story
+            .observe_variable(name, Rc::new(RefCell::new(observer)))?;
+        entry.observed.insert(name.to_string());
+        Ok(())
+    }
+
+    /// Copies into the story the values of the variables it shares by name
+    /// with `old`, e.g., to keep what the player has done across a reload. A
+    /// variable whose type changed keeps its new default, and one that can't
+    /// be set is warned about; the rest are still copied.
+    pub fn inherit_variables(&mut self, id: Entity, old: &Story) -> Result<(), InkError> {
+        let entry = self.entry_mut(id)?;
+        for name in &entry.variable_names {
+            let Some(value) = old.get_variable(name) else {
+                continue;
+            };
+            if entry
+                .story
+                .get_variable(name)
+                .is_some_and(|default| discriminant(&default) != discriminant(&value))
+            {
+                continue;
+            }
+            if let Err(err) = entry.story.set_variable(name, &value) {
+                warn!("Could not keep ink variable {name:?} in {id}: {err}");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Shared starting values for story variables, loaded from a `.inkvars.json`
+/// file of plain JSON values, e.g., `{"gold": 10, "name": "Ann",
+/// "inventory": ["Items.sword"]}`, so designers can keep presets as data.
+/// Whole numbers load as ints, other numbers as floats, and arrays of
+/// strings as lists.
+#[derive(Debug, Asset, TypePath, Clone, Default)]
+pub struct InkVarsAsset(pub HashMap<String, VariableValue>);
+
+/// Add to a story entity to set the variables of an [InkVarsAsset] as soon
+/// as its story is parsed, like [InkInitVars], which is applied after it so an
+/// entity can override its preset. [load_on_add_then_poll] waits for the
+/// preset to load as well as the story.
+#[derive(Debug, Component, Clone)]
+pub struct InkVarsPreset(pub Handle<InkVarsAsset>);
+
+/// Loads `.inkvars.json` files as [InkVarsAsset]s.
+#[derive(Debug, Default)]
+pub struct InkVarsLoader;
+
+impl AssetLoader for InkVarsLoader {
+    type Asset = InkVarsAsset;
+    type Settings = ();
+    type Error = InkError;
+
+    fn extensions(&self) -> &[&str] {
+        &["inkvars.json"]
+    }
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let json: serde_json::Value = serde_json::from_slice(&bytes)
+            .map_err(|err| InkError::BadVarsPreset(err.to_string()))?;
+        let serde_json::Value::Object(values) = json else {
+            return Err(InkError::BadVarsPreset("not a JSON object".into()));
+        };
+        values
+            .into_iter()
+            .map(|(name, value)| {
+                let value = variable_from_json(&value).ok_or_else(|| {
+                    InkError::BadVarsPreset(format!("{name:?} isn't an ink value"))
+                })?;
+                Ok((name, value))
+            })
+            .collect::<Result<_, _>>()
+            .map(InkVarsAsset)
+    }
+}
+
+fn variable_from_json(value: &serde_json::Value) -> Option<VariableValue> {
+    use serde_json::Value;
+    Some(match value {
+        Value::Bool(b) => VariableValue::Bool(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => VariableValue::Int(i32::try_from(i).ok()?),
+            None => VariableValue::Float(n.as_f64()? as f32),
+        },
+        Value::String(s) => VariableValue::String(s.clone()),
+        Value::Array(items) => VariableValue::List(
+            items
+                .iter()
+                .map(|item| item.as_str().map(String::from))
+                .collect::<Option<_>>()?,
+        ),
+        Value::Null | Value::Object(_) => return None,
+    })
+}
+
+fn variable_to_json(value: &VariableValue) -> serde_json::Value {
+    use serde_json::{Number, Value};
+    match value {
+        VariableValue::Bool(b) => Value::Bool(*b),
+        VariableValue::Int(i) => Value::from(*i),
+        VariableValue::Float(f) => {
+            Number::from_f64(f64::from(*f)).map_or(Value::Null, Value::Number)
+        }
+        VariableValue::String(s) | VariableValue::DivertTarget(s) => Value::String(s.clone()),
+        VariableValue::List(items) => items.iter().cloned().map(Value::String).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GREETING: &str = include_str!("../assets/greeting.ink.json");
+
+    #[test]
+    fn init_vars_are_set_before_the_first_line() {
+        let mut app = crate::tests::app();
+        let handle = app
+            .world_mut()
+            .resource_mut::<Assets<InkText>>()
+            .add(InkText(GREETING.into(), InkFormat::Json));
+        let vars =
+            HashMap::from_iter([("npc_name".to_string(), VariableValue::String("Bob".into()))]);
+        let id = app
+            .world_mut()
+            .spawn((InkLoad(handle), InkInitVars(vars)))
+            .id();
+        app.update();
+        app.update();
+
+        assert!(app.world().entity(id).contains::<InkStory>());
+        let mut ink_stories = app.world_mut().non_send_resource_mut::<InkStories>();
+        assert_eq!(ink_stories.cont(id).unwrap(), "Hi, Bob.\n");
+    }
+
+    #[test]
+    fn a_variable_whose_type_changed_is_not_inherited() {
+        let mut ink_stories = InkStories::default();
+        let id = Entity::from_raw(0);
+        ink_stories.parse_from_str(id, GREETING).unwrap();
+        let mut old = Story::new(GREETING).unwrap();
+        old.set_variable("npc_name", &ValueType::Int(3)).unwrap();
+
+        ink_stories.inherit_variables(id, &old).unwrap();
+        assert_eq!(ink_stories.cont(id).unwrap(), "Hi, Anon.\n");
+    }
+}